@@ -1,40 +1,102 @@
 extern crate phf;
 use phf::phf_map;
 
-#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms, non_camel_case_types)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum Intent {
+    CLOSE,
+    COMBINE,
+    DROP,
     EQUIP,
     EXAMINE,
     INVENTORY,
     INTERACT,
     LIST_INVENTORY,
+    LOCK,
     MOVEMENT,
+    OPEN,
+    ORDER,
+    PUT,
+    REDO,
+    RESTORE,
+    SAVE,
+    SEARCH,
+    SWITCH_OFF,
+    SWITCH_ON,
+    TALK,
+    UNDO,
+    UNEQUIP,
+    UNLOCK,
     USE,
+    #[default]
     NONE,
 }
 
-impl Default for Intent {
-    fn default() -> Self {
-        Intent::NONE
-    }
-}
-
 pub static LEGAL_COMMANDS: phf::Map<&'static str, Intent> = phf_map! {
+    "close" => Intent::CLOSE,
+    "shut" => Intent::CLOSE,
+    "attach" => Intent::COMBINE,
+    "combine" => Intent::COMBINE,
+    "discard" => Intent::DROP,
+    "drop" => Intent::DROP,
+    "put down" => Intent::DROP,
     "equip" => Intent::EQUIP,
+    "put on" => Intent::EQUIP,
+    "wear" => Intent::EQUIP,
+    "remove" => Intent::UNEQUIP,
+    "take off" => Intent::UNEQUIP,
+    "unequip" => Intent::UNEQUIP,
     "examine" => Intent::EXAMINE,
+    "inspect" => Intent::EXAMINE,
+    "look at" => Intent::EXAMINE,
+    "get" => Intent::INVENTORY,
+    "pick up" => Intent::INVENTORY,
     "pickup" => Intent::INVENTORY,
     "take" => Intent::INVENTORY,
     "grab" => Intent::INVENTORY,
+    "press" => Intent::INTERACT,
     "push" => Intent::INTERACT,
     "shake" => Intent::INTERACT,
+    "pull" => Intent::INTERACT,
     "touch" => Intent::INTERACT,
     "show" => Intent::LIST_INVENTORY,
     "list" => Intent::LIST_INVENTORY,
+    "lock" => Intent::LOCK,
+    "climb" => Intent::MOVEMENT,
+    "enter" => Intent::MOVEMENT,
     "exit" => Intent::MOVEMENT,
     "go" => Intent::MOVEMENT,
+    "leave" => Intent::MOVEMENT,
     "move" => Intent::MOVEMENT,
     "run" => Intent::MOVEMENT,
     "walk" => Intent::MOVEMENT,
+    "open" => Intent::OPEN,
+    "insert" => Intent::PUT,
+    "place" => Intent::PUT,
+    "put" => Intent::PUT,
+    "redo" => Intent::REDO,
+    "undo" => Intent::UNDO,
+    "save" => Intent::SAVE,
+    "load" => Intent::RESTORE,
+    "restore" => Intent::RESTORE,
+    "look around" => Intent::SEARCH,
+    "look in" => Intent::SEARCH,
+    "search" => Intent::SEARCH,
+    "extinguish" => Intent::SWITCH_OFF,
+    "put out" => Intent::SWITCH_OFF,
+    "switch off" => Intent::SWITCH_OFF,
+    "turn off" => Intent::SWITCH_OFF,
+    "light" => Intent::SWITCH_ON,
+    "switch on" => Intent::SWITCH_ON,
+    "turn on" => Intent::SWITCH_ON,
+    "order" => Intent::ORDER,
+    "tell" => Intent::ORDER,
+    "speak to" => Intent::TALK,
+    "speak with" => Intent::TALK,
+    "talk" => Intent::TALK,
+    "talk to" => Intent::TALK,
+    "talk with" => Intent::TALK,
+    "unlock" => Intent::UNLOCK,
     "swipe" => Intent::USE,
     "read" => Intent::USE,
     "use" => Intent::USE,
@@ -0,0 +1,383 @@
+use super::*;
+
+// What the player learns about the inside of a container when examining it
+pub fn describe_contents(game_state: &GameState, key: &str) -> Option<String> {
+    let container = game_state.inventory.get(key)?.get_container()?;
+
+    if !container.is_open() {
+        return Some("It is closed.".to_string());
+    }
+
+    let contents = game_state.items_in_container(key);
+    if contents.is_empty() {
+        Some("It is empty.".to_string())
+    } else {
+        Some(format!("It contains: {}", contents.join(", ")))
+    }
+}
+
+// Lists what is inside a container, as done by "open" and "search"
+fn list_contents(game_state: &GameState, key: &str, outcome: &mut Outcome) {
+    let contents = game_state.items_in_container(key);
+
+    if contents.is_empty() {
+        outcome.narrate(&format!("The {} is empty", key));
+    } else {
+        outcome.item_list(
+            &format!("Inside the {} you find:", key),
+            contents.iter().map(|name| name.to_string()).collect(),
+        );
+    }
+}
+
+// Adds what is inside an open container to the inventory listing, indented one level deeper than
+// the container. The contents of closed containers stay out of sight.
+pub fn list_carried_contents(game_state: &GameState, key: &str, depth: usize, lines: &mut Vec<String>) {
+    let is_open = game_state
+        .inventory
+        .get(key)
+        .and_then(Item::get_container)
+        .is_some_and(Container::is_open);
+    if !is_open {
+        return;
+    }
+
+    for name in game_state.items_in_container(key) {
+        let item = &game_state.inventory[name];
+        lines.push(format!(
+            "{}{}: {}",
+            "  ".repeat(depth),
+            item.get_name(),
+            render(item.get_description(), game_state)
+        ));
+        list_carried_contents(game_state, name, depth + 1, lines);
+    }
+}
+
+pub fn open_container(game_state: &mut GameState, key: &str, outcome: &mut Outcome) {
+    let container = match game_state.inventory.get_mut(key).and_then(Item::get_container_mut) {
+        Some(container) => container,
+        None => {
+            outcome.error(&format!("The {} can not be opened", key));
+            return;
+        }
+    };
+
+    if container.is_open() {
+        outcome.error(&format!("The {} is already open", key));
+    } else if container.is_locked() {
+        outcome.error(&format!("The {} is locked", key));
+    } else {
+        container.open();
+        outcome.narrate(&format!("You open the {}", key));
+        outcome.event(GameEvent::ContainerOpened(key.to_string()));
+        list_contents(game_state, key, outcome);
+    }
+}
+
+pub fn close_container(game_state: &mut GameState, key: &str, outcome: &mut Outcome) {
+    match game_state.inventory.get_mut(key).and_then(Item::get_container_mut) {
+        Some(container) if container.is_open() => {
+            container.close();
+            outcome.narrate(&format!("You close the {}", key));
+            outcome.event(GameEvent::ContainerClosed(key.to_string()));
+        }
+        Some(_) => outcome.error(&format!("The {} is already closed", key)),
+        None => outcome.error(&format!("The {} can not be closed", key)),
+    }
+}
+
+// Handles "lock <container>" and "unlock <container> [with <key>]". The key has to be carried
+pub fn set_container_lock(
+    game_state: &mut GameState,
+    parsed_input: &Input,
+    locked: bool,
+    outcome: &mut Outcome,
+) {
+    let key = match &parsed_input.direct_object {
+        Some(Object::Item(key)) => key.clone(),
+        _ => return,
+    };
+
+    let lock_key = match game_state
+        .inventory
+        .get::<str>(&key)
+        .and_then(Item::get_container)
+        .and_then(Container::get_key)
+    {
+        Some(lock_key) => lock_key.to_string(),
+        None => {
+            outcome.error(&format!("The {} has no lock", key));
+            return;
+        }
+    };
+
+    match &parsed_input.indirect_object {
+        Some(Object::Item(other)) if *other != lock_key => {
+            outcome.error(&format!("The {} does not fit the lock of the {}", other, key));
+            return;
+        }
+        Some(Object::Item(_)) | None => {}
+        Some(_) => {
+            outcome.error(&format!(
+                "You can not {} the {} with the {}",
+                parsed_input.verb, key, parsed_input.indirect_noun
+            ));
+            return;
+        }
+    }
+
+    let has_key = game_state
+        .inventory
+        .get::<str>(&lock_key)
+        .is_some_and(Item::is_carried);
+
+    let container = match game_state.inventory.get_mut::<str>(&key).and_then(Item::get_container_mut) {
+        Some(container) => container,
+        None => return,
+    };
+
+    if container.is_locked() == locked {
+        let state = if locked { "locked" } else { "unlocked" };
+        outcome.error(&format!("The {} is already {}", key, state));
+    } else if !has_key {
+        outcome.error(&format!("You need the {} to {} the {}", lock_key, parsed_input.verb, key));
+    } else if locked && container.is_open() {
+        outcome.error(&format!("You need to close the {} first", key));
+    } else if locked {
+        container.lock();
+        outcome.narrate(&format!("You lock the {} with the {}", key, lock_key));
+        outcome.event(GameEvent::ContainerLocked(key));
+    } else {
+        container.unlock();
+        outcome.narrate(&format!("You unlock the {} with the {}", key, lock_key));
+        outcome.event(GameEvent::ContainerUnlocked(key));
+    }
+}
+
+pub fn search_container(game_state: &GameState, key: &str, outcome: &mut Outcome) {
+    match game_state.inventory.get(key).and_then(Item::get_container) {
+        Some(container) if container.is_open() => list_contents(game_state, key, outcome),
+        Some(_) => outcome.error(&format!("The {} is closed", key)),
+        None => outcome.error(&format!("You find nothing inside the {}", key)),
+    }
+}
+
+// Handles "put <item> in <container>"
+pub fn put_item(game_state: &mut GameState, parsed_input: &Input, outcome: &mut Outcome) {
+    let key = match &parsed_input.direct_object {
+        Some(Object::Item(key)) => key.clone(),
+        _ => return,
+    };
+
+    let container_key = match &parsed_input.indirect_object {
+        Some(Object::Item(container_key)) => container_key.clone(),
+        Some(_) => {
+            outcome.error(&format!("You can not put things in the {}", parsed_input.indirect_noun));
+            return;
+        }
+        None => {
+            outcome.error(&format!("What do you want to put the {} in?", key));
+            return;
+        }
+    };
+
+    if !game_state.inventory.get::<str>(&key).is_some_and(Item::is_carried) {
+        outcome.error(&format!("You are not carrying the {}", key));
+        return;
+    }
+
+    match game_state.inventory.get::<str>(&container_key).and_then(Item::get_container) {
+        None => {
+            outcome.error(&format!("You can not put things in the {}", container_key));
+            return;
+        }
+        Some(container) if !container.is_open() => {
+            outcome.error(&format!("The {} is closed", container_key));
+            return;
+        }
+        Some(_) => {}
+    }
+
+    // A container can not end up inside itself, even through other containers
+    if key == container_key || game_state.is_inside(&container_key, &key) {
+        outcome.error(&format!("You can not put the {} inside itself", key));
+        return;
+    }
+
+    if let Some(item) = game_state.inventory.get_mut::<str>(&key) {
+        item.move_to_container(&container_key);
+        outcome.narrate(&format!("You put the {} in the {}", key, container_key));
+        outcome.event(GameEvent::ItemStored {
+            item: key,
+            container: container_key,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    // A room holding a locked chest with a bag inside, and a key in the player's inventory
+    fn create_container_game_state() -> GameState {
+        game_with_items(vec![
+            Item {
+                description: "A wooden chest".to_string(),
+                container: Some(Container {
+                    open: false,
+                    locked: true,
+                    key: Some("key".to_string()),
+                }),
+                fixed: true,
+                ..item("chest", ItemState::Room(0))
+            },
+            Item {
+                description: "A cloth bag".to_string(),
+                container: Some(Container {
+                    open: true,
+                    ..Default::default()
+                }),
+                ..item("bag", ItemState::Container("chest".to_string()))
+            },
+            item("disk", ItemState::Container("bag".to_string())),
+            item("key", ItemState::Inventory),
+        ])
+    }
+
+    #[test]
+    fn test_inventory_lists_open_containers() {
+        let mut game_state = create_container_game_state();
+        game_state.inventory.get_mut("bag").unwrap().move_to_inventory();
+
+        let listed_state = update(game_state.clone(), "show inventory".to_string());
+
+        assert_eq!(
+            listed_state.sys_message,
+            "Your inventory:\nbag: A cloth bag\n  disk: A disk\nkey: A key\nYou are carrying a weight of 0 and a bulk of 0"
+        );
+
+        game_state.inventory.get_mut("bag").unwrap().get_container_mut().unwrap().close();
+        let listed_state = update(game_state, "show inventory".to_string());
+
+        assert_eq!(
+            listed_state.sys_message,
+            "Your inventory:\nbag: A cloth bag\nkey: A key\nYou are carrying a weight of 0 and a bulk of 0"
+        );
+    }
+
+    #[test]
+    fn test_locked_container() {
+        let game_state = create_container_game_state();
+
+        let opened_state = update(game_state, "open chest".to_string());
+
+        assert_eq!(opened_state.sys_message, "The chest is locked");
+
+        let unlocked_state = update(opened_state, "unlock the chest with the key".to_string());
+
+        assert_eq!(unlocked_state.sys_message, "You unlock the chest with the key");
+
+        let opened_state = update(unlocked_state, "open chest".to_string());
+
+        assert_eq!(opened_state.sys_message, "You open the chest\nInside the chest you find:\nbag");
+    }
+
+    #[test]
+    fn test_closed_container_hides_contents() {
+        let game_state = create_container_game_state();
+
+        let after_state = update(game_state, "take bag".to_string());
+
+        assert_eq!(after_state.sys_message, "You don't see any bag here.");
+    }
+
+    #[test]
+    fn test_take_from_nested_container() {
+        let mut game_state = create_container_game_state();
+        game_state.inventory.get_mut("chest").unwrap().container = Some(Container {
+            open: true,
+            ..Default::default()
+        });
+
+        let wrong_state = update(game_state.clone(), "take disk from chest".to_string());
+
+        assert_eq!(wrong_state.sys_message, "The disk is not in the chest");
+
+        let taken_state = update(game_state, "take disk from bag".to_string());
+
+        assert_eq!(taken_state.sys_message, "You take the disk from the bag");
+        assert!(taken_state.inventory.get("disk").unwrap().is_in_inventory());
+    }
+
+    #[test]
+    fn test_put_item_in_container() {
+        let mut game_state = create_container_game_state();
+        game_state.inventory.get_mut("bag").unwrap().move_to_inventory();
+
+        let put_state = update(game_state, "put key in bag".to_string());
+
+        assert_eq!(put_state.sys_message, "You put the key in the bag");
+        assert!(put_state.inventory.get("key").unwrap().is_in_container("bag"));
+
+        let search_state = update(put_state, "look in bag".to_string());
+
+        assert_eq!(search_state.sys_message, "Inside the bag you find:\ndisk\nkey");
+
+        let closed_state = update(search_state, "close bag".to_string());
+        let examined_state = update(closed_state, "examine bag".to_string());
+
+        assert_eq!(examined_state.sys_message, "A cloth bag\nIt is closed.");
+    }
+
+    #[test]
+    fn test_container_can_not_hold_itself() {
+        let mut game_state = create_container_game_state();
+        game_state.inventory.get_mut("bag").unwrap().move_to_inventory();
+        game_state.inventory.get_mut("disk").unwrap().move_to_inventory();
+        game_state.inventory.get_mut("disk").unwrap().container = Some(Container {
+            open: true,
+            ..Default::default()
+        });
+
+        let nested_state = update(game_state, "put disk in bag".to_string());
+        let bag_state = update(nested_state, "put bag in disk".to_string());
+
+        assert_eq!(bag_state.sys_message, "You can not put the bag inside itself");
+
+        let mut game_state = create_container_game_state();
+        game_state.inventory.get_mut("bag").unwrap().move_to_inventory();
+
+        let self_state = update(game_state, "put bag in bag".to_string());
+
+        assert_eq!(self_state.sys_message, "You can not put the bag inside itself");
+    }
+
+    #[test]
+    fn test_fixed_container_can_not_be_taken() {
+        let mut game_state = create_container_game_state();
+        game_state.inventory.get_mut("chest").unwrap().container = Some(Container {
+            open: true,
+            ..Default::default()
+        });
+
+        let after_state = update(game_state, "take chest".to_string());
+
+        assert_eq!(after_state.sys_message, "The chest can not be moved");
+    }
+
+    #[test]
+    fn test_containers_survive_save() {
+        let game_state = create_container_game_state();
+        let unlocked_state = update(game_state, "unlock chest".to_string());
+        let opened_state = update(unlocked_state, "open chest".to_string());
+
+        let restored_state = GameState::restore(&opened_state.save()).unwrap();
+        let chest = restored_state.inventory.get("chest").unwrap().get_container().unwrap();
+
+        assert!(chest.is_open());
+        assert!(!chest.is_locked());
+        assert!(restored_state.inventory.get("disk").unwrap().is_in_container("bag"));
+    }
+}
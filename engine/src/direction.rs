@@ -1,7 +1,10 @@
 extern crate phf;
 use phf::phf_map;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq)]
+// The variant names are part of the save format
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub enum Direction {
     N,
     S,
@@ -11,12 +14,43 @@ pub enum Direction {
     NW,
     SE,
     SW,
+    U,
+    D,
+    IN,
+    OUT,
+    #[default]
     NONE,
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::NONE
+impl Direction {
+    /// The name players use for the direction, such as "north".
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Direction::N => "north",
+            Direction::S => "south",
+            Direction::E => "east",
+            Direction::W => "west",
+            Direction::NE => "northeast",
+            Direction::NW => "northwest",
+            Direction::SE => "southeast",
+            Direction::SW => "southwest",
+            Direction::U => "up",
+            Direction::D => "down",
+            Direction::IN => "in",
+            Direction::OUT => "out",
+            Direction::NONE => "nowhere",
+        }
+    }
+
+    /// Where something lies in this direction, as in "to the north" or "above".
+    pub fn get_position(&self) -> String {
+        match self {
+            Direction::U => "above".to_string(),
+            Direction::D => "below".to_string(),
+            Direction::IN => "inside".to_string(),
+            Direction::OUT => "outside".to_string(),
+            _ => format!("to the {}", self.get_name()),
+        }
     }
 }
 
@@ -29,6 +63,22 @@ static DIRECTION_MAPPINGS: phf::Map<&'static str, Direction> = phf_map! {
     "northwest" => Direction::NW,
     "southeast" => Direction::SE,
     "southwest" => Direction::SW,
+    "up" => Direction::U,
+    "down" => Direction::D,
+    "in" => Direction::IN,
+    "inside" => Direction::IN,
+    "out" => Direction::OUT,
+    "outside" => Direction::OUT,
+    "n" => Direction::N,
+    "s" => Direction::S,
+    "e" => Direction::E,
+    "w" => Direction::W,
+    "ne" => Direction::NE,
+    "nw" => Direction::NW,
+    "se" => Direction::SE,
+    "sw" => Direction::SW,
+    "u" => Direction::U,
+    "d" => Direction::D,
 };
 
 pub fn text_to_direction(text: &str) -> Option<Direction> {
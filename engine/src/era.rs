@@ -0,0 +1,158 @@
+use super::*;
+
+// The version of a room in an era. Rooms outside of any era are the same in all of them
+fn room_in_era(game_state: &GameState, room_idx: usize, era: &str) -> Option<usize> {
+    let room = &game_state.rooms[room_idx];
+    if room.era.is_empty() {
+        return Some(room_idx);
+    }
+
+    game_state
+        .rooms
+        .iter()
+        .position(|x| x.location == room.location && x.era == era)
+}
+
+// Where an exit leads in the current era. World files name one version of the target, so the
+// exit is followed to the version of its location the player is in now
+pub fn exit_target(game_state: &GameState, exit: &Exit) -> usize {
+    room_in_era(game_state, exit.target, &game_state.era).unwrap_or(exit.target)
+}
+
+// Moves the player to the version of their location in another era, such as the lab as it was
+// before it fell to ruin. Rooms outside of any era look the same in all of them, so the player
+// stays where they are
+pub fn change_era(game_state: &mut GameState, era: &str, outcome: &mut Outcome) {
+    if game_state.era == era {
+        return;
+    }
+
+    let room_idx = match room_in_era(game_state, game_state.current_room_idx, era) {
+        Some(room_idx) => room_idx,
+        None => {
+            outcome.error("For a moment the world around you wavers, then settles back as it was");
+            return;
+        }
+    };
+
+    game_state.era = era.to_string();
+    outcome.event(GameEvent::EraChanged(era.to_string()));
+
+    if room_idx != game_state.current_room_idx {
+        enter_room(game_state, room_idx, outcome);
+    }
+    outcome.room_description(&game_state.describe_room(room_idx));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn test_travel_to_the_past() {
+        let game_state = in_room(game_wearing_helmet(), "north_door_room");
+        let game_state = update(game_state, "touch the clock".to_string());
+        let past_room_idx = game_state.find_room("north_door_room_past").unwrap();
+
+        assert_eq!(game_state.era, "past");
+        assert_eq!(game_state.current_room_idx, past_room_idx);
+        assert!(game_state
+            .outcome
+            .events
+            .contains(&GameEvent::EraChanged("past".to_string())));
+        assert_eq!(
+            game_state.outcome.blocks.last(),
+            Some(&OutputBlock::RoomDescription(game_state.describe_room(past_room_idx)))
+        );
+        assert_eq!(
+            update(game_state.clone(), "take book".to_string()).sys_message,
+            "You don't see any book here."
+        );
+
+        let returned_state = update(game_state, "touch the clock".to_string());
+
+        assert_eq!(returned_state.era, "present");
+        assert_eq!(
+            returned_state.current_room_idx,
+            returned_state.find_room("north_door_room").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_items_and_flags_cross_eras() {
+        let game_state = in_room(game_wearing_helmet(), "north_door_room");
+        let game_state = update(game_state, "touch the clock".to_string());
+
+        assert!(game_state.inventory.get("helmet").unwrap().is_equipped());
+
+        let game_state = update(game_state, "drop pendant".to_string());
+        let game_state = update(game_state, "push the tile".to_string());
+
+        assert!(game_state.flags.contains("hid_the_tin"));
+
+        let returned_state = update(game_state, "touch the clock".to_string());
+        let room_idx = returned_state.current_room_idx;
+
+        assert!(!returned_state.inventory.get("pendant").unwrap().is_in_room(room_idx));
+        assert!(returned_state.inventory.get("tin").unwrap().is_in_room(room_idx));
+        assert!(returned_state
+            .describe_room(room_idx)
+            .contains("One of the floor tiles sits a little higher than the others."));
+    }
+
+    #[test]
+    fn test_exits_lead_into_the_current_era() {
+        let mut game_state = in_room(game_wearing_helmet(), "helmet_room");
+        let mut outcome = Outcome::default();
+
+        change_era(&mut game_state, "past", &mut outcome);
+
+        assert_eq!(game_state.current_room_idx, game_state.find_room("helmet_room").unwrap());
+
+        let moved_state = update(game_state, "go west".to_string());
+        let past_room_idx = moved_state.find_room("north_door_room_past").unwrap();
+
+        assert_eq!(moved_state.current_room_idx, past_room_idx);
+        assert_eq!(moved_state.rooms[past_room_idx].visits, 1);
+        assert_eq!(
+            moved_state.outcome.events,
+            vec![GameEvent::RoomEntered(past_room_idx)]
+        );
+    }
+
+    #[test]
+    fn test_followers_travel_across_eras() {
+        let mut game_state = in_room(game_wearing_helmet(), "north_door_room");
+        game_state.npcs[0].room_idx = game_state.current_room_idx;
+        game_state.npcs[0].follows = true;
+
+        let past_state = update(game_state, "touch the clock".to_string());
+        let past_room_idx = past_state.find_room("north_door_room_past").unwrap();
+
+        assert_eq!(past_state.npcs[0].room_idx, past_room_idx);
+        assert!(past_state.describe_room(past_room_idx).ends_with("\nAlso here: robot"));
+
+        let mut staying_state = update(past_state, "robot, wait".to_string());
+        let returned_state = update(staying_state.clone(), "touch the clock".to_string());
+
+        assert_eq!(returned_state.npcs[0].room_idx, past_room_idx);
+
+        staying_state.npcs[0].follows = true;
+        let returned_state = update(staying_state, "touch the clock".to_string());
+
+        assert_eq!(returned_state.npcs[0].room_idx, returned_state.current_room_idx);
+    }
+
+    #[test]
+    fn test_timeless_room_keeps_the_player() {
+        let mut game_state = game_with_items(vec![]);
+        let mut outcome = Outcome::default();
+
+        change_era(&mut game_state, "past", &mut outcome);
+
+        assert_eq!(game_state.era, "past");
+        assert_eq!(game_state.current_room_idx, 0);
+        assert_eq!(outcome.events, vec![GameEvent::EraChanged("past".to_string())]);
+    }
+}
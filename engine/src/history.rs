@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+pub const DEFAULT_HISTORY_DEPTH: usize = 20;
+
+/// Keeps the game states of previous turns so that the player can undo and redo them.
+#[derive(Clone, Debug)]
+pub struct History {
+    depth_limit: usize,
+    undo_stack: VecDeque<GameState>,
+    redo_stack: Vec<GameState>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl History {
+    /// Creates a history that remembers at most `depth_limit` turns.
+    pub fn new(depth_limit: usize) -> Self {
+        History {
+            depth_limit,
+            undo_stack: VecDeque::with_capacity(depth_limit),
+            redo_stack: vec![],
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Runs a turn like `update`, answering `undo` and `redo` from the recorded turns. Only
+    /// inputs that make a turn pass are recorded.
+    pub fn update(&mut self, prev_state: GameState, input: String) -> GameState {
+        // Parsed the same way as `update` parses it, so "Undo." is still an undo
+        let intent = parse_input(&input, &prev_state).ok().map(|x| x.intent);
+
+        match intent {
+            Some(Intent::UNDO) => match self.undo_stack.pop_back() {
+                Some(state) => {
+                    self.redo_stack.push(prev_state);
+                    state
+                }
+                None => update(prev_state, input),
+            },
+            Some(Intent::REDO) => match self.redo_stack.pop() {
+                Some(state) => {
+                    self.push_undo(prev_state);
+                    state
+                }
+                None => update(prev_state, input),
+            },
+            // Commands that were not understood and saving or restoring take no time, so they
+            // leave nothing to undo
+            None | Some(Intent::SAVE) | Some(Intent::RESTORE) => update(prev_state, input),
+            _ => {
+                let new_state = update(prev_state.clone(), input);
+                self.push_undo(prev_state);
+                self.redo_stack.clear();
+                new_state
+            }
+        }
+    }
+
+    fn push_undo(&mut self, state: GameState) {
+        if self.depth_limit == 0 {
+            return;
+        }
+
+        if self.undo_stack.len() == self.depth_limit {
+            self.undo_stack.pop_front();
+        }
+
+        self.undo_stack.push_back(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut history = History::default();
+        let game_state = update(start_game(), "examine door".to_string());
+
+        let moved_state = history.update(game_state.clone(), "use pendant".to_string());
+        let undone_state = history.update(moved_state.clone(), "undo".to_string());
+
+        assert!(undone_state.rooms[0].exits[0].is_locked());
+        assert_eq!(undone_state.sys_message, game_state.sys_message);
+
+        let redone_state = history.update(undone_state, "redo".to_string());
+
+        assert!(!redone_state.rooms[0].exits[0].is_locked());
+        assert_eq!(redone_state.sys_message, moved_state.sys_message);
+    }
+
+    #[test]
+    fn test_undo_skips_invalid_commands() {
+        let mut history = History::default();
+        let game_state = update(start_game(), "examine door".to_string());
+
+        let moved_state = history.update(game_state.clone(), "use pendant".to_string());
+        let confused_state = history.update(moved_state, "dance wildly".to_string());
+        let saved_state = history.update(confused_state, "save".to_string());
+
+        assert_eq!(history.undo_stack.len(), 1);
+
+        let undone_state = history.update(saved_state, "undo".to_string());
+
+        assert!(undone_state.rooms[0].exits[0].is_locked());
+        assert_eq!(undone_state.sys_message, game_state.sys_message);
+    }
+
+    #[test]
+    fn test_undo_with_punctuation() {
+        let mut history = History::default();
+        let game_state = update(start_game(), "examine door".to_string());
+
+        let moved_state = history.update(game_state, "use the rusty pendant".to_string());
+        let undone_state = history.update(moved_state, "  Undo.".to_string());
+
+        assert!(undone_state.rooms[0].exits[0].is_locked());
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn test_nothing_to_undo() {
+        let mut history = History::default();
+        let game_state = history.update(start_game(), "undo".to_string());
+
+        assert_eq!(game_state.sys_message, "There is nothing to undo.");
+
+        let game_state = history.update(game_state, "redo".to_string());
+
+        assert_eq!(game_state.sys_message, "There is nothing to redo.");
+    }
+
+    #[test]
+    fn test_new_turn_clears_redo() {
+        let mut history = History::default();
+        let game_state = history.update(start_game(), "show inventory".to_string());
+        let game_state = history.update(game_state, "undo".to_string());
+
+        assert!(history.can_redo());
+
+        history.update(game_state, "examine door".to_string());
+
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let mut history = History::new(2);
+        let mut game_state = start_game();
+
+        for _ in 0..5 {
+            game_state = history.update(game_state, "show inventory".to_string());
+        }
+
+        assert_eq!(history.undo_stack.len(), 2);
+
+        game_state = history.update(game_state, "undo".to_string());
+        game_state = history.update(game_state, "undo".to_string());
+        game_state = history.update(game_state, "undo".to_string());
+
+        assert_eq!(game_state.sys_message, "There is nothing to undo.");
+    }
+}
@@ -1,16 +1,125 @@
-use std::collections::HashMap;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+use super::Concealment;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ItemState {
-    Room,
+    // The index of the room the item is lying in
+    Room(usize),
     Inventory,
+    Equipped,
+    // The id of the container holding the item
+    Container(String),
+    // Items that have been used up or have not been placed in the world yet
+    #[default]
+    Nowhere,
+}
+
+/// Where on the player's body an item can be worn.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Slot {
+    Head,
+    Hands,
+    Back,
+}
+
+impl Slot {
+    pub fn get_name(&self) -> &str {
+        match self {
+            Slot::Head => "head",
+            Slot::Hands => "hands",
+            Slot::Back => "back",
+        }
+    }
+}
+
+/// How much the player can carry at once. A limit that is not set is unbounded.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CarryLimit {
+    #[serde(default)]
+    pub weight: Option<u32>,
+    #[serde(default)]
+    pub bulk: Option<u32>,
+}
+
+/// Lets an item hold other items, such as a box, a drawer or a bag.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Container {
+    #[serde(default)]
+    pub open: bool,
+    #[serde(default)]
+    pub locked: bool,
+    // The item that locks and unlocks the container. Containers without a key can not be locked
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+impl Container {
+    pub fn get_key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn open(&mut self) {
+        self.open = true
+    }
+
+    pub fn close(&mut self) {
+        self.open = false
+    }
+
+    pub fn lock(&mut self) {
+        self.locked = true
+    }
+
+    pub fn unlock(&mut self) {
+        self.locked = false
+    }
 }
 
-#[derive(Clone, Debug)]
+/// Lets an item give off light, such as a flashlight or a lamp, while it is switched on.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Light {
+    #[serde(default)]
+    pub on: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Item {
+    // Words the player can use to tell this item apart from others, such as "rusty"
+    #[serde(default)]
+    pub adjectives: Vec<String>,
     pub name: String,
     pub description: String,
     pub location: ItemState,
+    // The slot the item is worn in. Items without a slot can not be equipped
+    #[serde(default)]
+    pub slot: Option<Slot>,
+    // Set for items that can hold other items
+    #[serde(default)]
+    pub container: Option<Container>,
+    // Set for items that can be switched on to light up dark rooms
+    #[serde(default)]
+    pub light: Option<Light>,
+    // Items that are part of the room, such as a desk, can not be picked up
+    #[serde(default)]
+    pub fixed: bool,
+    // How heavy and how large the item is, counted against the player's CarryLimit
+    #[serde(default)]
+    pub weight: u32,
+    #[serde(default)]
+    pub bulk: u32,
+    // Whether the item has to be searched for before it can be seen
+    #[serde(flatten)]
+    pub concealment: Concealment,
 }
 
 impl Item {
@@ -26,54 +135,82 @@ impl Item {
         &self.name
     }
 
+    pub fn get_slot(&self) -> Option<&Slot> {
+        self.slot.as_ref()
+    }
+
+    pub fn get_container(&self) -> Option<&Container> {
+        self.container.as_ref()
+    }
+
+    pub fn get_container_mut(&mut self) -> Option<&mut Container> {
+        self.container.as_mut()
+    }
+
+    pub fn get_light_mut(&mut self) -> Option<&mut Light> {
+        self.light.as_mut()
+    }
+
+    // Whether the item is a light that is switched on
+    pub fn is_shining(&self) -> bool {
+        self.light.as_ref().is_some_and(|x| x.on)
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.fixed
+    }
+
+    pub fn get_weight(&self) -> u32 {
+        self.weight
+    }
+
+    pub fn get_bulk(&self) -> u32 {
+        self.bulk
+    }
+
     pub fn is_in_inventory(&self) -> bool {
         self.location == ItemState::Inventory
     }
 
-    pub fn to_inventory(&mut self) {
-        self.location = match self.location {
-            _ => ItemState::Inventory,
-        }
+    pub fn is_in_room(&self, room_idx: usize) -> bool {
+        self.location == ItemState::Room(room_idx)
     }
 
-    pub fn to_room(&mut self) {
-        self.location = match self.location {
-            _ => ItemState::Room,
+    pub fn is_in_container(&self, container: &str) -> bool {
+        match &self.location {
+            ItemState::Container(key) => key == container,
+            _ => false,
         }
     }
-}
 
-pub fn create_inventory() -> HashMap<&'static str, Item> {
-    let mut map = HashMap::new();
-
-    map.insert(
-        "helmet",
-        Item {
-            name: "helmet".to_string(),
-            description: "a blue helmet covered in dirt".to_string(),
-            location: ItemState::Room,
-        },
-    );
-
-    map.insert(
-        "buster",
-        Item {
-            name: "buster".to_string(),
-            description: "A large cannon with four buttons".to_string(),
-            location: ItemState::Room,
-        },
-    );
-
-    map.insert(
-        "pendant",
-        Item {
-            name: "pendant".to_string(),
-            description: "A rusty pendant with a small seal on it.".to_string(),
-            location: ItemState::Inventory,
-        },
-    );
+    pub fn is_equipped(&self) -> bool {
+        self.location == ItemState::Equipped
+    }
+
+    // Whether the player has the item on them, either in the inventory or equipped
+    pub fn is_carried(&self) -> bool {
+        self.is_in_inventory() || self.is_equipped()
+    }
+
+    pub fn equip(&mut self) {
+        self.location = ItemState::Equipped
+    }
+
+    pub fn move_to_inventory(&mut self) {
+        self.location = ItemState::Inventory
+    }
 
-    map
+    pub fn move_to_room(&mut self, room_idx: usize) {
+        self.location = ItemState::Room(room_idx)
+    }
+
+    pub fn move_to_container(&mut self, container: &str) {
+        self.location = ItemState::Container(container.to_string())
+    }
+
+    pub fn move_to_nowhere(&mut self) {
+        self.location = ItemState::Nowhere
+    }
 }
 
 #[cfg(test)]
@@ -87,7 +224,8 @@ mod tests {
         let new_item = Item {
             name: "test".to_string(),
             description: expected.clone(),
-            location: ItemState::Room,
+            location: ItemState::Room(0),
+            ..Default::default()
         };
 
         assert_eq!(new_item.get_description(), expected);
@@ -100,7 +238,8 @@ mod tests {
         let new_item = Item {
             name: expected.clone(),
             description: "test desc".to_string(),
-            location: ItemState::Room,
+            location: ItemState::Room(0),
+            ..Default::default()
         };
 
         assert_eq!(new_item.get_name(), expected);
@@ -108,29 +247,63 @@ mod tests {
 
     #[test]
     fn test_get_location() {
-        let expected = ItemState::Room;
+        let expected = ItemState::Room(0);
 
         let new_item = Item {
             name: "test".to_string(),
             description: "test desc".to_string(),
-            location: ItemState::Room,
+            location: ItemState::Room(0),
+            ..Default::default()
         };
 
         assert_eq!(new_item.get_location(), &expected);
     }
 
     #[test]
-    fn test_to_room() {
-        let expected = ItemState::Room;
+    fn test_move_to_room() {
+        let expected = ItemState::Room(2);
 
         let mut new_item = Item {
             name: "test".to_string(),
             description: "test desc".to_string(),
             location: ItemState::Inventory,
+            ..Default::default()
         };
 
-        new_item.to_room();
+        new_item.move_to_room(2);
 
         assert_eq!(new_item.get_location(), &expected);
     }
+
+    #[test]
+    fn test_equip() {
+        let mut new_item = Item {
+            name: "test".to_string(),
+            description: "test desc".to_string(),
+            location: ItemState::Inventory,
+            slot: Some(Slot::Head),
+            ..Default::default()
+        };
+
+        new_item.equip();
+
+        assert!(new_item.is_equipped());
+        assert!(new_item.is_carried());
+        assert!(!new_item.is_in_inventory());
+    }
+
+    #[test]
+    fn test_to_container() {
+        let mut new_item = Item {
+            name: "test".to_string(),
+            location: ItemState::Inventory,
+            ..Default::default()
+        };
+
+        new_item.move_to_container("box");
+
+        assert!(new_item.is_in_container("box"));
+        assert!(!new_item.is_in_container("bag"));
+        assert!(!new_item.is_carried());
+    }
 }
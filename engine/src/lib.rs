@@ -1,28 +1,104 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[path = "commands.rs"]
 mod commands;
 
+#[path = "container.rs"]
+mod container;
+
 #[path = "direction.rs"]
 mod direction;
 
+#[path = "era.rs"]
+mod era;
+
 #[path = "examine.rs"]
 mod examine;
 
+#[path = "history.rs"]
+mod history;
+
 #[path = "item.rs"]
 mod item;
 
+#[path = "light.rs"]
+mod light;
+
+#[path = "memory.rs"]
+mod memory;
+
+#[path = "npc.rs"]
+mod npc;
+
+#[path = "outcome.rs"]
+mod outcome;
+
+#[path = "parser.rs"]
+mod parser;
+
+#[path = "prompt.rs"]
+mod prompt;
+
+#[path = "recipe.rs"]
+mod recipe;
+
+#[path = "save.rs"]
+mod save;
+
+#[path = "search.rs"]
+mod search;
+
+#[path = "template.rs"]
+mod template;
+
+#[path = "trigger.rs"]
+mod trigger;
+
+#[path = "world.rs"]
+mod world;
+
+#[cfg(test)]
+#[path = "test_helpers.rs"]
+mod test_helpers;
+
 use commands::*;
+use container::*;
 use direction::*;
+use era::*;
 use examine::*;
+pub use history::{History, DEFAULT_HISTORY_DEPTH};
+pub use direction::Direction;
 use item::*;
-
-#[derive(Clone, Debug)]
+use light::*;
+use memory::*;
+use npc::*;
+pub use outcome::{GameEvent, Outcome, OutputBlock};
+pub use parser::ParseError;
+use parser::{parse_input, tokenize, Input, Object};
+use prompt::*;
+use recipe::*;
+use search::*;
+pub use save::{SaveError, SystemRequest, SAVE_FORMAT_VERSION};
+use save::{restore_game, save_game};
+use serde_derive::{Deserialize, Serialize};
+use template::*;
+use trigger::*;
+pub use world::{WorldError, DEFAULT_WORLD, WORLD_FORMAT_VERSION};
+use world::load_world;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct Exit {
     direction: Direction,
     locked: bool,
     interactable_id: String,
     target: usize,
+    #[serde(flatten)]
+    concealment: Concealment,
+    // What the player can call the exit instead of its direction, as in "climb the ladder"
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    adjectives: Vec<String>,
 }
 
 impl Exit {
@@ -33,33 +109,100 @@ impl Exit {
     fn unlock(&mut self) {
         self.locked = false
     }
+
+    fn lock(&mut self) {
+        self.locked = true
+    }
 }
 
-#[derive(Clone, Debug)]
+/// Whether the game is still going on, and how it ended if it is not.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum GameStatus {
+    #[default]
+    Playing,
+    // The id of the ending the player reached
+    Won(String),
+    // Why the player lost
+    Lost(String),
+}
+
+/// One of the ways the story can end, authored in the world file. Its text is shown when a
+/// trigger wins the game with it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Ending {
+    pub id: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct GameState {
     pub current_room_idx: usize,
-    pub inventory: HashMap<&'static str, Item>,
+    pub inventory: HashMap<String, Item>,
+    #[serde(default)]
+    pub carry_limit: CarryLimit,
+    // The item combinations authored in the world file
+    #[serde(default)]
+    pub recipes: Vec<Recipe>,
+    // Story flags and numeric variables set by triggers
+    #[serde(default)]
+    pub flags: BTreeSet<String>,
+    #[serde(default)]
+    pub variables: BTreeMap<String, i64>,
+    // The number of turns played so far
+    #[serde(default)]
+    pub turn: u32,
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+    #[serde(default)]
+    pub endings: Vec<Ending>,
+    #[serde(default)]
+    pub npcs: Vec<Npc>,
+    #[serde(default)]
+    pub memories: Vec<Memory>,
+    // The ids of the memories the player has been in
+    #[serde(default)]
+    pub memories_seen: BTreeSet<String>,
+    // The memory being played, which takes the place of the room until the player leaves it
+    #[serde(default)]
+    pub scene: Option<String>,
+    // The era the player is in, for worlds with more than one version of a location
+    #[serde(default)]
+    pub era: String,
+    #[serde(default)]
+    pub status: GameStatus,
+    // A question waiting for the player's answer
+    #[serde(default)]
+    pub prompt: Option<Prompt>,
     pub sys_message: String,
     pub rooms: Vec<Room>,
+    // Set when the player asks the frontend to persist or reload the game
+    #[serde(skip)]
+    pub system_request: Option<SystemRequest>,
+    // What happened during the last turn. sys_message is the plain text form of it
+    #[serde(skip)]
+    pub outcome: Outcome,
 }
 
-#[derive(Debug, Default)]
-struct Input {
-    intent: Intent,
-    is_direction: bool,
-    is_interactable: bool,
-    is_item: bool,
-    object_noun: String,
-}
-
-#[derive(Clone, Debug)]
-struct Interactable {
-    after_interaction_description: &'static str,
-    before_interaction_description: &'static str,
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Interactable {
+    #[serde(default)]
+    adjectives: Vec<String>,
+    after_interaction_description: String,
+    before_interaction_description: String,
+    #[serde(flatten)]
+    concealment: Concealment,
+    // Extra text shown when examining this while the named item is equipped
+    #[serde(default)]
+    equipped_descriptions: HashMap<String, String>,
     id: String,
-    interaction_description: &'static str,
+    interaction_description: String,
+    #[serde(default)]
     interacted: bool,
+    // The id of a memory the player enters after interacting with this
+    #[serde(default)]
+    memory: String,
     name: String,
+    #[serde(default)]
     prerequisite_item: String,
 }
 
@@ -74,21 +217,38 @@ impl Interactable {
 }
 
 impl Examine for Interactable {
-    fn examine(&self) -> &'static str {
+    fn examine(&self) -> &str {
         if self.interacted {
-            self.after_interaction_description
+            &self.after_interaction_description
         } else {
-            self.before_interaction_description
+            &self.before_interaction_description
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Room {
+    // The id of the room in the world file
+    #[serde(default)]
+    id: String,
     description: String,
+    // Extra text added to the description while the named item is equipped
+    #[serde(default)]
+    equipped_descriptions: HashMap<String, String>,
     interactables: Vec<Interactable>,
-    items: Vec<&'static str>,
     exits: Vec<Exit>,
+    // Rooms that share a location are versions of the same place in different eras. Rooms
+    // without an era are the same in all of them
+    #[serde(default)]
+    location: String,
+    #[serde(default)]
+    era: String,
+    // Dark rooms can only be seen in with a light that is switched on
+    #[serde(default)]
+    dark: bool,
+    // How many times the player has been in the room
+    #[serde(default)]
+    visits: u32,
 }
 
 impl Room {
@@ -97,334 +257,764 @@ impl Room {
     }
 }
 
-pub fn start_game() -> GameState {
-    let rooms = vec![
-            Room {
-                description: "A wind blows over the dunes of sand that cover the known world as you step up to a large dilapidated building.
-
-Unlike other ruins you have seen in the past, this structure does not speak of a lavish past.
-
-You are greeted with a metal door weathered from the years and bearing a strange insignia.".to_string(),
-                exits: vec![
-                    Exit {
-                        direction: Direction::S,
-                        interactable_id: "lab_entrance".to_string(),
-                        locked: true,
-                        target: 2,
-                    },
-                ],
-                interactables: vec![Interactable{id: "lab_entrance".to_string(), name: "door".to_string(), interaction_description: "The pendant fits into the panel in the door.
-You hear a brief beeping sound and see a few lights on the panel turn from red to green.
-The door swings open to the south.", before_interaction_description: "You notice a small panel to the side of the door with what seems to be a slot to fit something in.", after_interaction_description: "The door has slid open and exposed a path to the south.", interacted: false, prerequisite_item: "pendant".to_string()}],
-                items: vec![],
-            },
-            Room {
-                description: "You find yourself in a room. There is a door to the west and a door to the south. You notice a small crevice in the corner.  The room with the helmet".to_string(),
-                exits: vec![
-                    Exit {
-                        direction: Direction::W,
-                        interactable_id: "".to_string(),
-                        locked: false,
-                        target: 0,
-                    },
-                    Exit {
-                        direction: Direction::S,
-                        interactable_id: "".to_string(),
-                        locked: false,
-                        target: 3,
-                    },
-                ],
-                interactables: vec![],
-                items: vec!["helmet"],
-            },
-            Room {
-                description: "You find yourself in a room. There is a door to the north".to_string(),
-                exits: vec![
-                    Exit {
-                        direction: Direction::N,
-                        interactable_id: "".to_string(),
-                        target: 0,
-                        locked: false,
-                    },
-                ],
-                interactables: vec![],
-                items: vec![],
-            },
-            Room {
-                description: "You find yourself in a room. There is a door to the north. The door to the south is locked.".to_string(),
-                exits: vec![
-                    Exit {
-                        direction: Direction::N,
-                        interactable_id: "".to_string(),
-                        target: 1,
-                        locked: false,
-                    },
-                    Exit {
-                        direction: Direction::S,
-                        interactable_id: "".to_string(),
-                        target: 4,
-                        locked: false,
-                    },
-                ],
-                interactables: vec![],
-                items: vec![],
-            },
-            Room {
-                description: "Dungeon exit".to_string(),
-                exits: vec![],
-                interactables: vec![],
-                items: vec![],
+// Appends the descriptions that are only visible while a particular item is equipped
+fn with_equipment(
+    description: &str,
+    equipped_descriptions: &HashMap<String, String>,
+    inventory: &HashMap<String, Item>,
+) -> String {
+    let mut item_names: Vec<&String> = equipped_descriptions
+        .keys()
+        .filter(|name| inventory.get(*name).is_some_and(Item::is_equipped))
+        .collect();
+    item_names.sort();
+
+    let mut text = description.to_string();
+    for name in item_names {
+        text.push('\n');
+        text.push_str(&equipped_descriptions[name]);
     }
-        ];
 
-    return GameState {
-        current_room_idx: 0,
-        inventory: create_inventory(),
-        rooms: rooms,
-        sys_message: "".to_string(),
-    };
+    text
 }
 
-pub fn update(prev_state: GameState, input: String) -> GameState {
-    let mut parsed_input = Input {
-        ..Default::default()
-    };
+// Shows an amount against its limit, such as "4/20", or just the amount when there is no limit
+fn with_limit(amount: u32, limit: Option<u32>) -> String {
+    match limit {
+        Some(limit) => format!("{}/{}", amount, limit),
+        None => amount.to_string(),
+    }
+}
 
-    let mut new_game_state = prev_state.clone();
+impl GameState {
+    pub fn from_world_str(world: &str) -> Result<GameState, WorldError> {
+        load_world(world)
+    }
 
-    // use the new_game_state instead of previous so that we modify the new_game_state when
-    // interacting.
-    // This is fine since we just have a cloned previous state here
-    let room = &mut new_game_state.rooms[new_game_state.current_room_idx];
-    let user_inventory = &mut new_game_state.inventory;
+    pub fn save(&self) -> String {
+        save_game(self)
+    }
 
-    let mut user_input = input.split_whitespace().peekable();
-    let first_command = user_input.next().unwrap();
+    pub fn restore(save: &str) -> Result<GameState, SaveError> {
+        restore_game(save)
+    }
 
-    if !is_legal_command(first_command) {
-        // If the command is not valid, we do not need to parse the rest of the string input
-        new_game_state.sys_message = format!("{} is not a legal command\n", first_command);
-        return new_game_state;
-    };
+    // The index of the room with the given world file id
+    fn find_room(&self, id: &str) -> Option<usize> {
+        self.rooms.iter().position(|room| room.id == id)
+    }
 
-    parsed_input.intent = determine_intent(first_command).unwrap();
+    /// The ending the player reached, once the game has been won.
+    pub fn ending(&self) -> Option<&Ending> {
+        match &self.status {
+            GameStatus::Won(id) => self.endings.iter().find(|x| &x.id == id),
+            _ => None,
+        }
+    }
 
-    for word in user_input {
-        let lowercase_word = word.to_lowercase();
-        if parsed_input.object_noun == "" {
-            if lowercase_word == "inventory" {
-                parsed_input.object_noun = lowercase_word;
-                continue;
-            }
+    /// The names of the items lying in a room, sorted by name. Fixed items are left out since
+    /// they are part of the room itself.
+    pub fn items_in_room(&self, room_idx: usize) -> Vec<&str> {
+        if !self.is_lit(room_idx) {
+            return vec![];
+        }
 
-            if is_direction(lowercase_word.as_str()) {
-                parsed_input.object_noun = lowercase_word;
-                parsed_input.is_direction = true;
-                continue;
-            }
+        let mut items: Vec<&str> = self
+            .inventory
+            .values()
+            .filter(|item| item.is_in_room(room_idx) && !item.is_fixed())
+            .filter(|item| item.concealment.is_visible())
+            .map(Item::get_name)
+            .collect();
+        items.sort();
 
-            if user_inventory.contains_key(lowercase_word.as_str()) {
-                parsed_input.object_noun = lowercase_word;
-                parsed_input.is_item = true;
-                continue;
+        items
+    }
+
+    /// The names of the items directly inside a container, sorted by name.
+    pub fn items_in_container(&self, key: &str) -> Vec<&str> {
+        let mut items: Vec<&str> = self
+            .inventory
+            .values()
+            .filter(|item| item.is_in_container(key))
+            .map(Item::get_name)
+            .collect();
+        items.sort();
+
+        items
+    }
+
+    /// Whether the player can see in a room: it is not dark, or a light that is switched on lies
+    /// in it or is carried by the player while they are in it.
+    pub fn is_lit(&self, room_idx: usize) -> bool {
+        !self.rooms[room_idx].dark
+            || self.inventory.values().any(|item| {
+                item.is_shining()
+                    && (item.is_in_room(room_idx) || (item.is_carried() && room_idx == self.current_room_idx))
+            })
+    }
+
+    /// Whether the player can see and reach an item: it is carried, lies in the current room or
+    /// sits in an open container that is itself within reach.
+    pub fn is_reachable(&self, key: &str) -> bool {
+        let mut item = match self.inventory.get(key) {
+            Some(item) if item.concealment.is_visible() => item,
+            _ => return false,
+        };
+
+        // Bounded so that containers nested inside each other can not loop forever
+        for _ in 0..self.inventory.len() {
+            match item.get_location() {
+                ItemState::Inventory | ItemState::Equipped => return true,
+                ItemState::Room(room_idx) => {
+                    return *room_idx == self.current_room_idx && self.is_lit(*room_idx)
+                }
+                ItemState::Container(container_key) => match self.inventory.get(container_key) {
+                    Some(container) if container.get_container().is_some_and(Container::is_open) => {
+                        item = container
+                    }
+                    _ => return false,
+                },
+                ItemState::Nowhere => return false,
             }
+        }
+
+        false
+    }
 
-            if room.interactables.iter().any(|x| x.name == lowercase_word) {
-                parsed_input.object_noun = lowercase_word;
-                parsed_input.is_interactable = true;
-                continue;
+    // Whether an item is inside a container, either directly or through other containers
+    fn is_inside(&self, key: &str, container_key: &str) -> bool {
+        let mut location = self.inventory.get(key).map(Item::get_location);
+
+        for _ in 0..self.inventory.len() {
+            match location {
+                Some(ItemState::Container(parent)) if parent == container_key => return true,
+                Some(ItemState::Container(parent)) => {
+                    location = self.inventory.get(parent).map(Item::get_location)
+                }
+                _ => return false,
             }
         }
+
+        false
+    }
+
+    // Whether the player has the item on them, either directly or inside a carried container
+    fn is_held(&self, key: &str) -> bool {
+        self.inventory
+            .iter()
+            .any(|(holder, item)| item.is_carried() && (holder == key || self.is_inside(key, holder)))
+    }
+
+    // The weight and bulk of an item together with everything inside it
+    fn load_of(&self, key: &str) -> (u32, u32) {
+        self.inventory
+            .iter()
+            .filter(|(name, _)| name.as_str() == key || self.is_inside(name, key))
+            .fold((0, 0), |(weight, bulk), (_, item)| {
+                (weight + item.get_weight(), bulk + item.get_bulk())
+            })
+    }
+
+    /// The total weight and bulk of everything the player carries, including the contents of
+    /// carried containers.
+    pub fn carried_load(&self) -> (u32, u32) {
+        self.inventory
+            .iter()
+            .filter(|(_, item)| item.is_carried())
+            .map(|(key, _)| self.load_of(key))
+            .fold((0, 0), |(weight, bulk), (item_weight, item_bulk)| {
+                (weight + item_weight, bulk + item_bulk)
+            })
+    }
+
+    // How much the player carries compared to what they can carry, as shown with the inventory
+    fn describe_load(&self) -> String {
+        let (weight, bulk) = self.carried_load();
+
+        format!(
+            "You are carrying a weight of {} and a bulk of {}",
+            with_limit(weight, self.carry_limit.weight),
+            with_limit(bulk, self.carry_limit.bulk)
+        )
+    }
+
+    /// The description of a room as the player currently perceives it, including the items
+    /// lying there.
+    pub fn describe_room(&self, room_idx: usize) -> String {
+        let room = &self.rooms[room_idx];
+
+        if !self.is_lit(room_idx) {
+            return describe_darkness(self, room_idx);
+        }
+
+        let mut description = render(
+            &with_equipment(&room.description, &room.equipped_descriptions, &self.inventory),
+            self,
+        );
+
+        let items = self.items_in_room(room_idx);
+        if !items.is_empty() {
+            description.push_str(&format!("\nOn the ground you see: {}", items.join(", ")));
+        }
+
+        // Hidden things are not part of the authored description, so they are listed once found
+        let mut found: Vec<String> = room
+            .interactables
+            .iter()
+            .filter(|x| x.concealment.hidden && x.concealment.found)
+            .map(|x| x.name.clone())
+            .collect();
+        found.extend(
+            room.exits
+                .iter()
+                .filter(|x| x.concealment.hidden && x.concealment.found)
+                .map(|x| format!("a way {}", x.direction.get_name())),
+        );
+        if !found.is_empty() {
+            description.push_str(&format!("\nYou have found: {}", found.join(", ")));
+        }
+
+        let npcs: Vec<&str> = self
+            .npcs
+            .iter()
+            .filter(|x| x.room_idx == room_idx)
+            .map(|x| x.name.as_str())
+            .collect();
+        if !npcs.is_empty() {
+            description.push_str(&format!("\nAlso here: {}", npcs.join(", ")));
+        }
+
+        description
     }
+}
 
-    if parsed_input.object_noun.is_empty() {
-        new_game_state.sys_message = format!("I was unable to understand your command.  Please re-enter and try again.")    
+pub fn start_game() -> GameState {
+    GameState::from_world_str(DEFAULT_WORLD).expect("the default world file is invalid")
+}
+
+pub fn update(prev_state: GameState, input: String) -> GameState {
+    let mut new_game_state = prev_state.clone();
+    new_game_state.system_request = None;
+
+    let mut outcome = Outcome::default();
+
+    if new_game_state.status != GameStatus::Playing {
+        outcome.error("The game is over.");
+        new_game_state.sys_message = outcome.message();
+        new_game_state.outcome = outcome;
+        return new_game_state;
     }
 
+    if new_game_state.prompt.is_some() && !passes_prompt(&new_game_state, &input) {
+        let answered = answer_prompt(&mut new_game_state, &input, &mut outcome);
+        if answered {
+            new_game_state.turn += 1;
+        }
+        run_triggers(&mut new_game_state, &mut outcome, answered);
+
+        new_game_state.sys_message = outcome.message();
+        new_game_state.outcome = outcome;
+        return new_game_state;
+    }
+
+    // Time stands still while the player is in a memory
+    if new_game_state.scene.is_some() {
+        play_scene(&mut new_game_state, &input, &mut outcome);
+        run_triggers(&mut new_game_state, &mut outcome, false);
+
+        new_game_state.sys_message = outcome.message();
+        new_game_state.outcome = outcome;
+        return new_game_state;
+    }
+
+    // A prompt that is still pending here waits for its answer while a system command runs
+    let pending_prompt = new_game_state.prompt.clone();
+
+    let parsed_input = match parse_input(&input, &new_game_state) {
+        Ok(parsed_input) => parsed_input,
+        Err(err) => {
+            outcome.error(&err.to_string());
+            new_game_state.sys_message = outcome.message();
+            new_game_state.outcome = outcome;
+            return new_game_state;
+        }
+    };
+
+    // use the new_game_state instead of previous so that we modify the new_game_state when
+    // interacting.
+    // This is fine since we just have a cloned previous state here
+    let room_idx = new_game_state.current_room_idx;
+    let room = &mut new_game_state.rooms[room_idx];
+    let user_inventory = &mut new_game_state.inventory;
+
     match parsed_input.intent {
-        Intent::EXAMINE => {
-            if parsed_input.is_interactable {
-                let description = room
-                    .interactables
-                    .iter()
-                    .find(|x| x.name == parsed_input.object_noun)
-                    .unwrap()
-                    .examine();
-
-                new_game_state.sys_message = description.to_string();
-            } else if parsed_input.is_item {
-                let description = user_inventory
-                    .get::<str>(&parsed_input.object_noun)
-                    .unwrap()
-                    .get_description();
-                new_game_state.sys_message = description.to_string();
+        Intent::EXAMINE => match &parsed_input.direct_object {
+            Some(Object::Interactable(id)) => {
+                if let Some(x) = room.interactables.iter().find(|x| &x.id == id) {
+                    let description =
+                        with_equipment(x.examine(), &x.equipped_descriptions, user_inventory);
+                    outcome.narrate(&render(&description, &new_game_state));
+                }
             }
-        }
+            Some(Object::Item(key)) => {
+                if let Some(item) = user_inventory.get::<str>(key) {
+                    let description = item.get_description().to_string();
+                    let mut description = render(&description, &new_game_state);
+                    if let Some(contents) = describe_contents(&new_game_state, key) {
+                        description.push('\n');
+                        description.push_str(&contents);
+                    }
+                    outcome.narrate(&description);
+                }
+            }
+            Some(Object::Npc(id)) => {
+                if let Some(npc) = new_game_state.npcs.iter().find(|x| &x.id == id) {
+                    outcome.narrate(&render(npc.examine(), &new_game_state));
+                }
+            }
+            _ => {}
+        },
         Intent::INTERACT => {
-            if parsed_input.is_interactable {
-                let inter_pos = room
-                    .interactables
-                    .iter()
-                    .position(|x| x.name == parsed_input.object_noun)
-                    .unwrap();
-
-                new_game_state.sys_message = match room.interactables.get_mut(inter_pos) {
-                    Some(x) => {
-                        if x.prerequisite_item.is_empty() {
-                            x.interact();
-                            x.interaction_description.to_string()
-                        } else {
-                            format!(
-                                "You currently can not interact with {}",
-                                room.interactables[inter_pos].name.clone()
-                            )
+            if let Some(Object::Interactable(id)) = &parsed_input.direct_object {
+                if let Some(x) = room.interactables.iter_mut().find(|x| &x.id == id) {
+                    // Equipment such as the helmet lets the player interact with some things
+                    // directly
+                    let has_equipped_prerequisite = user_inventory
+                        .get::<str>(&x.prerequisite_item)
+                        .is_some_and(Item::is_equipped);
+
+                    if x.prerequisite_item.is_empty() || has_equipped_prerequisite {
+                        x.interact();
+                        let id = x.id.clone();
+                        let description = x.interaction_description.clone();
+                        let memory = x.memory.clone();
+                        outcome.narrate(&render(&description, &new_game_state));
+                        outcome.event(GameEvent::InteractableTriggered(id));
+                        if !memory.is_empty() {
+                            enter_memory(&mut new_game_state, &memory, &mut outcome);
                         }
-                    },
-                    None => { 
-                        format!("There is no {} in this room", parsed_input.object_noun) 
-                    },
+                    } else {
+                        outcome.error(&format!("You currently can not interact with {}", x.name));
+                    }
                 }
             }
         }
-        Intent::INVENTORY => {
-            let key = &parsed_input.object_noun;
-            if parsed_input.is_item {
-                let item = user_inventory.get_mut::<str>(key).unwrap();
-                if *item.get_location() == ItemState::Room {
-                    item.to_inventory();
-
-                    new_game_state.sys_message =
-                        format!("You have picked up a {}", item.get_name().to_string());
-                } else {
-                    new_game_state.sys_message =
-                        format!("You already have the {}", item.get_name().to_string());
-                }
+        Intent::TALK => {
+            if let Some(Object::Npc(id)) = &parsed_input.direct_object {
+                talk_to(&mut new_game_state, id, &mut outcome);
+            }
+        }
+        Intent::ORDER => {
+            if let Some(Object::Npc(id)) = &parsed_input.direct_object {
+                give_order(&mut new_game_state, id, &parsed_input.order, &mut outcome);
             }
         }
+        Intent::INVENTORY => take_item(&mut new_game_state, &parsed_input, &mut outcome),
         Intent::LIST_INVENTORY => {
-            let initial_msg = "Your inventory:\n";
-            let mut inventory_message: String = "".to_string();
-            inventory_message.push_str(initial_msg);
-            for item in user_inventory.values() {
-                // If the item isn't in the Room, it is either in the user's inventory or equipped
-                // since there are currently only three states
-                if *item.get_location() != ItemState::Room {
-                    inventory_message.push_str(&format!(
-                        "{}: {}\n",
+            let mut carried: Vec<&Item> = new_game_state
+                .inventory
+                .values()
+                .filter(|item| item.is_carried())
+                .collect();
+            // The inventory is a HashMap, so the items are sorted to list them in a stable order
+            carried.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+            let mut inventory_items = vec![];
+            for item in carried {
+                let description = render(item.get_description(), &new_game_state);
+                inventory_items.push(match item.get_slot() {
+                    Some(slot) if item.is_equipped() => format!(
+                        "{}: {} (worn on your {})",
                         item.get_name(),
-                        item.get_description()
-                    ));
-                }
+                        description,
+                        slot.get_name()
+                    ),
+                    _ => format!("{}: {}", item.get_name(), description),
+                });
+                list_carried_contents(&new_game_state, item.get_name(), 1, &mut inventory_items);
             }
 
-            new_game_state.sys_message = match inventory_message == initial_msg {
-                true => "You have no items in your inventory".to_string(),
-                false => inventory_message,
-            };
+            if inventory_items.is_empty() {
+                outcome.narrate("You have no items in your inventory");
+            } else {
+                outcome.item_list("Your inventory:", inventory_items);
+                outcome.narrate(&new_game_state.describe_load());
+            }
         }
         Intent::MOVEMENT => {
-            if parsed_input.is_direction {
-                let direction: Direction = text_to_direction(&parsed_input.object_noun).unwrap();
-
-                let exit: Option<&Exit> = room.exits.iter().find(|&x| x.direction == direction);
-
-                if exit.is_none() {
-                    new_game_state.sys_message =
-                        format!("There is no exit leaving {}", parsed_input.object_noun);
-                } else if exit.unwrap().is_locked() {
-                    new_game_state.sys_message =
-                        format!("The way is locked. You must unlock the path before you proceed.");
-                } else if parsed_input.is_direction && exit.is_some() {
-                    new_game_state.current_room_idx = exit.unwrap().target;
-                    new_game_state.sys_message = new_game_state.rooms
-                        [new_game_state.current_room_idx]
-                        .description
-                        .to_string();
+            if let Some(Object::Direction(direction)) = &parsed_input.direct_object {
+                let exit = room
+                    .exits
+                    .iter()
+                    .find(|&x| &x.direction == direction && x.concealment.is_visible())
+                    .cloned();
+
+                match exit {
+                    // In the dark the player can only find their way toward a lit room
+                    _ if !new_game_state.is_lit(room_idx)
+                        && !exit
+                            .as_ref()
+                            .is_some_and(|x| new_game_state.is_lit(exit_target(&new_game_state, x))) =>
+                    {
+                        stumble(room_idx, &mut outcome);
+                    }
+                    None => {
+                        outcome.error(&format!("There is no exit leaving {}", direction.get_name()));
+                    }
+                    Some(exit) if exit.is_locked() => {
+                        outcome.error("The way is locked. You must unlock the path before you proceed.");
+                    }
+                    Some(exit) => {
+                        let target = exit_target(&new_game_state, &exit);
+                        enter_room(&mut new_game_state, target, &mut outcome);
+                        outcome.room_description(&new_game_state.describe_room(target));
+                    }
                 }
-            } else if !parsed_input.is_direction {
-                new_game_state.sys_message =
-                    format!("There is no path to the {}", parsed_input.object_noun);
             }
         }
-        Intent::USE => { 
-            let is_in_inventory = match user_inventory.get::<str>(&parsed_input.object_noun) {
-                Some(x) => x.is_in_inventory(),
-                None => false,
-            };
+        Intent::DROP => {
+            if let Some(Object::Item(key)) = &parsed_input.direct_object {
+                match user_inventory.get_mut::<str>(key) {
+                    Some(item) if item.is_carried() => {
+                        item.move_to_room(room_idx);
+                        outcome.narrate(&format!("You drop the {}", item.get_name()));
+                        outcome.event(GameEvent::ItemDropped(item.get_name().to_string()));
+                    }
+                    _ => outcome.error(&format!("You are not carrying the {}", key)),
+                }
+            }
+        }
+        Intent::PUT => put_item(&mut new_game_state, &parsed_input, &mut outcome),
+        Intent::COMBINE => combine_items(&mut new_game_state, &parsed_input, &mut outcome),
+        Intent::OPEN => {
+            if let Some(Object::Item(key)) = &parsed_input.direct_object {
+                open_container(&mut new_game_state, key, &mut outcome);
+            }
+        }
+        Intent::CLOSE => {
+            if let Some(Object::Item(key)) = &parsed_input.direct_object {
+                close_container(&mut new_game_state, key, &mut outcome);
+            }
+        }
+        Intent::LOCK => set_container_lock(&mut new_game_state, &parsed_input, true, &mut outcome),
+        Intent::UNLOCK => set_container_lock(&mut new_game_state, &parsed_input, false, &mut outcome),
+        Intent::SWITCH_ON => switch_light(&mut new_game_state, &parsed_input, true, &mut outcome),
+        Intent::SWITCH_OFF => switch_light(&mut new_game_state, &parsed_input, false, &mut outcome),
+        Intent::SEARCH => match &parsed_input.direct_object {
+            Some(Object::Item(key)) => search_container(&new_game_state, key, &mut outcome),
+            _ => search_room(&mut new_game_state, &mut outcome),
+        },
+        Intent::EQUIP => {
+            if let Some(Object::Item(key)) = &parsed_input.direct_object {
+                equip_item(user_inventory, key, &mut outcome);
+            }
+        }
+        Intent::UNEQUIP => {
+            if let Some(Object::Item(key)) = &parsed_input.direct_object {
+                match user_inventory.get_mut::<str>(key) {
+                    Some(item) if item.is_equipped() => {
+                        item.move_to_inventory();
+                        outcome.narrate(&format!("You take off the {}", item.get_name()));
+                        outcome.event(GameEvent::ItemUnequipped(item.get_name().to_string()));
+                    }
+                    _ => outcome.error(&format!("You are not wearing the {}", key)),
+                }
+            }
+        }
+        Intent::USE => use_item(&mut new_game_state, &parsed_input, &mut outcome),
+        Intent::SAVE => {
+            new_game_state.system_request = Some(SystemRequest::Save);
+            outcome.narrate("Your game has been saved.");
+        }
+        Intent::RESTORE => {
+            new_game_state.system_request = Some(SystemRequest::Restore);
+            outcome.narrate("Restoring your saved game...");
+        }
+        // Turns can only be undone or redone through a History
+        Intent::UNDO => outcome.error("There is nothing to undo."),
+        Intent::REDO => outcome.error("There is nothing to redo."),
+        // The parser never produces this intent
+        Intent::NONE => {}
+    }
 
+    if let Some(prompt) = &pending_prompt {
+        ask(&mut new_game_state, prompt, &mut outcome);
+    }
 
-            let inter_pos = room
-                    .interactables
-                    .iter()
-                    .position(|x| x.prerequisite_item == parsed_input.object_noun);
-
-            // is_some check is used to ensure that the interactable is actually in this room
-            new_game_state.sys_message = if parsed_input.is_item && is_in_inventory && inter_pos.is_some() {
-                match room.interactables.get_mut(inter_pos.unwrap()) {
-                    Some(x) => {
-                        if x.is_interacted() {
-                            format!("{} has already been used here", x.prerequisite_item)
-                        } else {
-                            // unlock room if it is dependent on the interactable_id
-                            let room_pos = room.exits.iter().position(|exit| exit.interactable_id == x.id).unwrap();
-                            match room.exits.get_mut(room_pos) {
-                                Some(exit) => exit.unlock(),
-                                None => println!("silently do not unlock the exit"),
-                            };
-                            x.interact(); 
-                            // set the item to the room because it has been used and can not be
-                            // used again
-                            user_inventory.get_mut::<str>(&parsed_input.object_noun).unwrap().to_room();
-                            x.interaction_description.to_string()
-                        }
-                    },
-                    None => format!("There is no use for the item {} in this room", parsed_input.object_noun),
-                }
-            // check for if the item is in your inventory first in order to not let the player know 
-            // the item is required here if they don't have the item
-            } else if !is_in_inventory {
-                format!("You have no item of that name in your inventory")
-            } else {
-                format!("You can not use `{}` here", parsed_input.object_noun)
-            };
+    // Saving, restoring and moving through the history do not take any time in the game, and
+    // neither does anything done while a prompt waits for its answer
+    let turn_passed = pending_prompt.is_none()
+        && !matches!(
+            parsed_input.intent,
+            Intent::SAVE | Intent::RESTORE | Intent::UNDO | Intent::REDO
+        );
+    if turn_passed {
+        new_game_state.turn += 1;
+    }
+    run_triggers(&mut new_game_state, &mut outcome, turn_passed);
+
+    new_game_state.sys_message = outcome.message();
+    new_game_state.outcome = outcome;
+
+    new_game_state
+}
+
+// Takes the player into another room, along with the characters that follow them
+fn enter_room(game_state: &mut GameState, target: usize, outcome: &mut Outcome) {
+    let room_idx = game_state.current_room_idx;
+    for npc in game_state
+        .npcs
+        .iter_mut()
+        .filter(|x| x.follows && x.room_idx == room_idx)
+    {
+        npc.room_idx = target;
+    }
+
+    game_state.current_room_idx = target;
+    game_state.rooms[target].visits += 1;
+    outcome.event(GameEvent::RoomEntered(target));
+}
+
+// Handles "take <item>" and "take <item> from <container>"
+fn take_item(game_state: &mut GameState, parsed_input: &Input, outcome: &mut Outcome) {
+    let key = match &parsed_input.direct_object {
+        Some(Object::Item(key)) => key.clone(),
+        _ => return,
+    };
+
+    let is_reachable = game_state.is_reachable(&key);
+
+    // Items that are taken out of a carried container are already part of the load
+    let (carried_weight, carried_bulk) = game_state.carried_load();
+    let (weight, bulk) = if game_state.is_held(&key) {
+        (0, 0)
+    } else {
+        game_state.load_of(&key)
+    };
+    let limit = game_state.carry_limit.clone();
+
+    let item = match game_state.inventory.get_mut::<str>(&key) {
+        Some(item) => item,
+        None => return,
+    };
+
+    let container_key = match &parsed_input.indirect_object {
+        Some(Object::Item(container_key)) if item.is_in_container(container_key) => {
+            Some(container_key.clone())
+        }
+        Some(_) => {
+            outcome.error(&format!("The {} is not in the {}", key, parsed_input.indirect_noun));
+            return;
+        }
+        None => None,
+    };
+
+    if item.is_carried() {
+        outcome.error(&format!("You already have the {}", item.get_name()));
+    } else if !is_reachable {
+        outcome.error(&format!("There is no {} here", item.get_name()));
+    } else if item.is_fixed() {
+        outcome.error(&format!("The {} can not be moved", item.get_name()));
+    } else if limit.weight.is_some_and(|limit| carried_weight + weight > limit) {
+        outcome.error(&format!(
+            "The {} is too heavy to carry along with everything else. Drop something first.",
+            item.get_name()
+        ));
+    } else if limit.bulk.is_some_and(|limit| carried_bulk + bulk > limit) {
+        outcome.error(&format!(
+            "You have no room left for the {}. Drop something first.",
+            item.get_name()
+        ));
+    } else {
+        item.move_to_inventory();
+
+        match container_key {
+            Some(container_key) => {
+                outcome.narrate(&format!("You take the {} from the {}", item.get_name(), container_key))
+            }
+            None => outcome.narrate(&format!("You have picked up a {}", item.get_name())),
+        }
+        outcome.event(GameEvent::ItemTaken(item.get_name().to_string()));
+    }
+}
+
+fn equip_item(inventory: &mut HashMap<String, Item>, key: &str, outcome: &mut Outcome) {
+    let slot = match inventory.get(key) {
+        Some(item) if item.is_equipped() => {
+            outcome.error(&format!("You are already wearing the {}", item.get_name()));
+            return;
+        }
+        Some(item) if !item.is_carried() => {
+            outcome.error(&format!("You need to pick up the {} first", item.get_name()));
+            return;
+        }
+        Some(item) => match item.get_slot() {
+            Some(slot) => slot.clone(),
+            None => {
+                outcome.error(&format!("The {} is not something you can wear", item.get_name()));
+                return;
+            }
         },
-        _ => new_game_state.sys_message = format!("You didn't choose an appropriate command"),
+        None => return,
+    };
+
+    // Only one item can be worn in each slot
+    if let Some(worn) = inventory
+        .values()
+        .find(|x| x.is_equipped() && x.get_slot() == Some(&slot))
+    {
+        outcome.error(&format!(
+            "You are already wearing the {} on your {}",
+            worn.get_name(),
+            slot.get_name()
+        ));
+        return;
+    }
+
+    if let Some(item) = inventory.get_mut(key) {
+        item.equip();
+        outcome.narrate(&format!("You put on the {}", item.get_name()));
+        outcome.event(GameEvent::ItemEquipped(item.get_name().to_string()));
     }
+}
+
+// Handles "use <item>" and "use <item> on <target>", where the target is an interactable or the
+// exit in a direction
+fn use_item(game_state: &mut GameState, parsed_input: &Input, outcome: &mut Outcome) {
+    let room_idx = game_state.current_room_idx;
+    let room = &mut game_state.rooms[room_idx];
+
+    let key = match &parsed_input.direct_object {
+        Some(Object::Item(key)) => key.clone(),
+        _ => return,
+    };
+
+    // check for if the item is in your inventory first in order to not let the player know
+    // the item is required here if they don't have the item
+    let is_in_inventory = match game_state.inventory.get::<str>(&key) {
+        Some(x) => x.is_carried(),
+        None => false,
+    };
+
+    if !is_in_inventory {
+        outcome.error("You have no item of that name in your inventory");
+        return;
+    }
+
+    let inter_pos = match &parsed_input.indirect_object {
+        // Without a target, use the item on the first lock in the room that it fits
+        None => match room
+            .interactables
+            .iter()
+            .position(|x| x.prerequisite_item == key && !x.is_interacted())
+            .or_else(|| room.interactables.iter().position(|x| x.prerequisite_item == key))
+        {
+            Some(pos) => pos,
+            None => {
+                outcome.error(&format!("You can not use `{}` here", key));
+                return;
+            }
+        },
+        Some(Object::Interactable(id)) => match room.interactables.iter().position(|x| &x.id == id) {
+            Some(pos) => pos,
+            None => return,
+        },
+        Some(Object::Direction(direction)) => {
+            let exit = match room
+                .exits
+                .iter()
+                .find(|x| &x.direction == direction && x.concealment.is_visible())
+            {
+                Some(exit) => exit,
+                None => {
+                    outcome.error(&format!("There is no exit leaving {}", parsed_input.indirect_noun));
+                    return;
+                }
+            };
 
-    return new_game_state;
+            match room.interactables.iter().position(|x| x.id == exit.interactable_id) {
+                Some(pos) => pos,
+                None => {
+                    outcome.error(&format!(
+                        "There is nothing to use the {} on to the {}",
+                        key, parsed_input.indirect_noun
+                    ));
+                    return;
+                }
+            }
+        }
+        Some(_) => {
+            outcome.error(&format!(
+                "You can not use the {} on the {}",
+                key, parsed_input.indirect_noun
+            ));
+            return;
+        }
+    };
+
+    let x = &mut room.interactables[inter_pos];
+
+    if x.prerequisite_item != key {
+        outcome.error(&format!("The {} does not seem to work on the {}", key, x.name));
+    } else if x.is_interacted() {
+        outcome.error(&format!("{} has already been used here", x.prerequisite_item));
+    } else {
+        // unlock the exits that depend on the interactable_id, if there are any
+        for exit in room.exits.iter_mut().filter(|exit| exit.interactable_id == x.id) {
+            exit.unlock();
+            outcome.event(GameEvent::ExitUnlocked {
+                room_idx,
+                direction: exit.direction.clone(),
+            });
+        }
+        x.interact();
+        let id = x.id.clone();
+        let description = x.interaction_description.clone();
+        // leave the item in the room because it has been used and can not be used again
+        if let Some(item) = game_state.inventory.get_mut::<str>(&key) {
+            item.move_to_room(room_idx);
+        }
+        outcome.narrate(&render(&description, game_state));
+        outcome.event(GameEvent::InteractableTriggered(id));
+        outcome.event(GameEvent::ItemUsed(key));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn create_test_inventory() -> HashMap<&'static str, Item> {
+    fn create_test_inventory() -> HashMap<String, Item> {
     let mut map = HashMap::new();
 
     map.insert(
-        "helmet",
+        "helmet".to_string(),
         Item {
             name: "helmet".to_string(),
             description: "a blue helmet covered in dirt".to_string(),
-            location: ItemState::Room,
+            location: ItemState::Room(0),
+            ..Default::default()
         },
     );
 
     map.insert(
-        "buster",
+        "buster".to_string(),
         Item {
             name: "buster".to_string(),
             description: "A large cannon with four buttons".to_string(),
-            location: ItemState::Room,
+            location: ItemState::Room(0),
+            ..Default::default()
         },
     );
 
     map.insert(
-        "pendant",
+        "pendant".to_string(),
         Item {
             name: "pendant".to_string(),
             description: "A rusty pendant with a small seal on it.".to_string(),
             location: ItemState::Inventory,
+            ..Default::default()
         },
     );
 
@@ -439,29 +1029,34 @@ mod tests {
                 interactable_id: "".to_string(),
                 target: 1,
                 locked: false,
+                ..Default::default()
             }],
             interactables: vec![],
-            items: vec!["helmet"],
+            ..Default::default()
         }];
 
         GameState {
             current_room_idx: 0,
             inventory: create_test_inventory(),
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         }
     }
 
     #[test]
     fn test_locked_exit() {
         let new_inter = Interactable {
-            after_interaction_description: "The stone is sitting on the floor",
-            before_interaction_description: "You see a stone sitting in between two logs",
+            after_interaction_description: "The stone is sitting on the floor".to_string(),
+            before_interaction_description: "You see a stone sitting in between two logs".to_string(),
             id: "lab_stone".to_string(),
             interacted: false,
-            interaction_description: "The stone rolls onto the floor",
+            interaction_description: "The stone rolls onto the floor".to_string(),
             name: "stone".to_string(),
             prerequisite_item: "".to_string(),
+            ..Default::default()
         };
 
         let rooms = vec![Room {
@@ -471,16 +1066,20 @@ mod tests {
                 interactable_id: "".to_string(),
                 target: 1,
                 locked: true,
+                ..Default::default()
             }],
             interactables: vec![new_inter],
-            items: vec![],
+            ..Default::default()
         }];
 
         let game_state = GameState {
             current_room_idx: 0,
             inventory: create_test_inventory(),
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         };
 
         let before_state = update(game_state, "go south".to_string());
@@ -491,21 +1090,16 @@ mod tests {
     #[test]
     fn test_use_to_unlock() {
        let new_inter = Interactable {
-           after_interaction_description: "The stone is sitting on the floor",
-            before_interaction_description: "You see a stone sitting in between two logs",
+           after_interaction_description: "The stone is sitting on the floor".to_string(),
+            before_interaction_description: "You see a stone sitting in between two logs".to_string(),
             id: "lab_stone".to_string(),
             interacted: false,
-            interaction_description: "The stone rolls onto the floor",
+            interaction_description: "The stone rolls onto the floor".to_string(),
             name: "stone".to_string(),
             prerequisite_item: "helmet".to_string(),
+           ..Default::default()
        };
 
-        let new_item = Item {
-            name: "helmet".to_string(),
-            description: "A large, blue helmet".to_string(),
-            location: ItemState::Inventory,
-        };
-
         let rooms = vec![
             Room {
                 description: "Test Room 1".to_string(),
@@ -514,9 +1108,10 @@ mod tests {
                     interactable_id: "lab_stone".to_string(),
                     target: 1,
                     locked: true,
+                    ..Default::default()
                 }],
                 interactables: vec![new_inter],
-                items: vec!["helmet"],
+                ..Default::default()
             },
             Room {
                 description: "Test Room 2".to_string(),
@@ -525,9 +1120,10 @@ mod tests {
                     interactable_id: "".to_string(),
                     target: 0,
                     locked: false,
+                    ..Default::default()
                 }],
                 interactables: vec![],
-                items: vec![],
+                ..Default::default()
             }
         ];
 
@@ -536,9 +1132,12 @@ mod tests {
 
         let game_state = GameState {
             current_room_idx: 0,
-            inventory: inventory,
+            inventory,
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         };
 
         let expected_after_description = "The stone rolls onto the floor";
@@ -548,19 +1147,20 @@ mod tests {
         assert_eq!(after_interacted_state.sys_message, expected_after_description);
 
         // ensure that the location is changed after using the helmet
-        assert_eq!(after_interacted_state.inventory.get("helmet").unwrap().get_location(), &ItemState::Room);
+        assert_eq!(after_interacted_state.inventory.get("helmet").unwrap().get_location(), &ItemState::Room(0));
     }
 
     #[test]
     fn test_interact() {
         let new_inter = Interactable {
-            after_interaction_description: "The stone is sitting on the floor",
-            before_interaction_description: "You see a stone sitting in between two logs",
+            after_interaction_description: "The stone is sitting on the floor".to_string(),
+            before_interaction_description: "You see a stone sitting in between two logs".to_string(),
             id: "lab_stone".to_string(),
             interacted: false,
-            interaction_description: "The stone rolls onto the floor",
+            interaction_description: "The stone rolls onto the floor".to_string(),
             name: "stone".to_string(),
             prerequisite_item: "".to_string(),
+            ..Default::default()
         };
 
         let rooms = vec![Room {
@@ -570,16 +1170,20 @@ mod tests {
                 interactable_id: "".to_string(),
                 target: 1,
                 locked: false,
+                ..Default::default()
             }],
             interactables: vec![new_inter],
-            items: vec![],
+            ..Default::default()
         }];
 
         let game_state = GameState {
             current_room_idx: 0,
             inventory: create_test_inventory(),
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         };
 
         let expected_after_interactable_description = "The stone is sitting on the floor";
@@ -608,13 +1212,14 @@ mod tests {
     #[test]
     fn test_no_interactable() {
         let new_inter = Interactable {
-            after_interaction_description: "The stone is sitting on the floor",
-            before_interaction_description: "You see a stone sitting in between two logs",
+            after_interaction_description: "The stone is sitting on the floor".to_string(),
+            before_interaction_description: "You see a stone sitting in between two logs".to_string(),
             id: "lab_stone".to_string(),
             interacted: false,
-            interaction_description: "The stone rolls onto the floor",
+            interaction_description: "The stone rolls onto the floor".to_string(),
             name: "stone".to_string(),
             prerequisite_item: "".to_string(),
+            ..Default::default()
         };
 
         let rooms = vec![Room {
@@ -624,19 +1229,23 @@ mod tests {
                 interactable_id: "".to_string(),
                 target: 1,
                 locked: false,
+                ..Default::default()
             }],
             interactables: vec![new_inter],
-            items: vec![],
+            ..Default::default()
         }];
 
         let game_state = GameState {
             current_room_idx: 0,
             inventory: create_test_inventory(),
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         };
 
-        let expected_interactable_description = "I was unable to understand your command.  Please re-enter and try again.";
+        let expected_interactable_description = "You don't see any face here.";
         let after_state = update(game_state, "examine face".to_string());
 
         assert_eq!(
@@ -655,9 +1264,10 @@ mod tests {
                     interactable_id: "".to_string(),
                     target: 1,
                     locked: false,
+                    ..Default::default()
                 }],
                 interactables: vec![],
-                items: vec![],
+                ..Default::default()
             },
             Room {
                 description: "Test Room 2".to_string(),
@@ -666,9 +1276,10 @@ mod tests {
                     interactable_id: "".to_string(),
                     target: 0,
                     locked: false,
+                    ..Default::default()
                 }],
                 interactables: vec![],
-                items: vec![],
+                ..Default::default()
             },
         ];
 
@@ -676,7 +1287,10 @@ mod tests {
             current_room_idx: 0,
             inventory: create_test_inventory(),
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         };
 
         let next_game_state = update(game_state, "go south".to_string());
@@ -690,12 +1304,6 @@ mod tests {
 
     #[test]
     fn test_update_inventory() {
-        let new_item = Item {
-            name: "helmet".to_string(),
-            description: "A large, blue helmet".to_string(),
-            location: ItemState::Room,
-        };
-
         let rooms = vec![Room {
             description: "Test Room 1".to_string(),
             exits: vec![Exit {
@@ -703,16 +1311,20 @@ mod tests {
                 interactable_id: "".to_string(),
                 target: 1,
                 locked: false,
+                ..Default::default()
             }],
             interactables: vec![],
-            items: vec!["helmet"],
+            ..Default::default()
         }];
 
         let game_state = GameState {
             current_room_idx: 0,
             inventory: create_test_inventory(),
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         };
 
         let before_state = update(game_state.clone(), "grab helmet".to_string());
@@ -725,6 +1337,102 @@ mod tests {
         assert_eq!(expected_sys_message, after_state.sys_message);
     }
 
+    #[test]
+    fn test_pick_up_only_in_item_room() {
+        let mut game_state = create_base_game_state();
+        game_state.inventory.get_mut("helmet").unwrap().move_to_room(1);
+
+        let after_state = update(game_state, "grab helmet".to_string());
+
+        assert_eq!(after_state.sys_message, "You don't see any helmet here.");
+        assert!(!after_state.inventory.get("helmet").unwrap().is_carried());
+    }
+
+    #[test]
+    fn test_carry_limit() {
+        let mut game_state = create_base_game_state();
+        game_state.carry_limit = CarryLimit {
+            weight: Some(10),
+            bulk: None,
+        };
+        game_state.inventory.get_mut("pendant").unwrap().weight = 2;
+        game_state.inventory.get_mut("buster").unwrap().weight = 9;
+        game_state.inventory.get_mut("helmet").unwrap().weight = 3;
+
+        let refused_state = update(game_state, "grab buster".to_string());
+
+        assert_eq!(
+            refused_state.sys_message,
+            "The buster is too heavy to carry along with everything else. Drop something first."
+        );
+        assert!(!refused_state.inventory.get("buster").unwrap().is_carried());
+
+        let helmet_state = update(refused_state, "grab helmet".to_string());
+        let dropped_state = update(helmet_state, "drop pendant".to_string());
+        let buster_state = update(dropped_state, "grab buster".to_string());
+
+        assert!(!buster_state.inventory.get("buster").unwrap().is_carried());
+
+        let helmet_dropped_state = update(buster_state, "drop helmet".to_string());
+        let buster_state = update(helmet_dropped_state, "grab buster".to_string());
+
+        assert_eq!(buster_state.sys_message, "You have picked up a buster");
+        assert_eq!(buster_state.carried_load(), (9, 0));
+    }
+
+    #[test]
+    fn test_list_inventory_shows_load() {
+        let mut game_state = create_base_game_state();
+        game_state.carry_limit = CarryLimit {
+            weight: Some(10),
+            bulk: Some(4),
+        };
+        game_state.inventory.get_mut("pendant").unwrap().weight = 2;
+        game_state.inventory.get_mut("pendant").unwrap().bulk = 1;
+
+        let after_state = update(game_state, "show inventory".to_string());
+
+        assert!(after_state
+            .sys_message
+            .ends_with("You are carrying a weight of 2/10 and a bulk of 1/4"));
+    }
+
+    #[test]
+    fn test_drop() {
+        let game_state = create_base_game_state();
+
+        let dropped_state = update(game_state, "drop the pendant".to_string());
+
+        assert_eq!(dropped_state.sys_message, "You drop the pendant");
+        assert!(dropped_state.inventory.get("pendant").unwrap().is_in_room(0));
+        assert_eq!(
+            dropped_state.outcome.events,
+            vec![GameEvent::ItemDropped("pendant".to_string())]
+        );
+        assert_eq!(
+            dropped_state.describe_room(0),
+            "Test Room 1\nOn the ground you see: buster, helmet, pendant"
+        );
+
+        let taken_state = update(dropped_state, "grab pendant".to_string());
+
+        assert!(taken_state.inventory.get("pendant").unwrap().is_in_inventory());
+    }
+
+    #[test]
+    fn test_drop_equipped_item() {
+        let mut game_state = create_base_game_state();
+        game_state.inventory.get_mut("helmet").unwrap().equip();
+
+        let after_state = update(game_state, "drop helmet".to_string());
+
+        assert!(after_state.inventory.get("helmet").unwrap().is_in_room(0));
+
+        let again_state = update(after_state, "drop helmet".to_string());
+
+        assert_eq!(again_state.sys_message, "You are not carrying the helmet");
+    }
+
     #[test]
     fn test_list_inventory() {
         let rooms = vec![Room {
@@ -734,24 +1442,28 @@ mod tests {
                 interactable_id: "".to_string(),
                 target: 1,
                 locked: false,
+                ..Default::default()
             }],
             interactables: vec![],
-            items: vec!["helmet"],
+            ..Default::default()
         }];
 
         let game_state = GameState {
             current_room_idx: 0,
             inventory: create_test_inventory(),
             sys_message: "".to_string(),
-            rooms: rooms,
+            rooms,
+            system_request: None,
+            outcome: Outcome::default(),
+            ..Default::default()
         };
 
         let before_state = update(game_state.clone(), "grab helmet".to_string());
         let new_game_state = update(before_state.clone(), "list inventory".to_string());
 
-        let expected_sys_message = "Your inventory:\nhelmet: a blue helmet covered in dirt\npendant: A rusty pendant with a small seal on it.\n";
+        let expected_sys_message = "Your inventory:\nhelmet: a blue helmet covered in dirt\npendant: A rusty pendant with a small seal on it.\nYou are carrying a weight of 0 and a bulk of 0";
 
-        assert!(new_game_state.sys_message.contains("helmet: a blue helmet") && new_game_state.sys_message.contains("pendant: A rusty pendant"));
+        assert_eq!(expected_sys_message, new_game_state.sys_message);
     }
 
     #[test]
@@ -768,4 +1480,150 @@ mod tests {
 
         assert!(no_inventory_state.sys_message.contains("You have no items in your inventory"));
     }
+
+    fn create_two_lock_game_state() -> GameState {
+        let stone = Interactable {
+            after_interaction_description: "The stone is sitting on the floor".to_string(),
+            before_interaction_description: "You see a stone sitting in between two logs".to_string(),
+            id: "lab_stone".to_string(),
+            interacted: false,
+            interaction_description: "The stone rolls onto the floor".to_string(),
+            name: "stone".to_string(),
+            prerequisite_item: "helmet".to_string(),
+            ..Default::default()
+        };
+
+        let panel = Interactable {
+            adjectives: vec!["control".to_string()],
+            after_interaction_description: "The panel glows green".to_string(),
+            before_interaction_description: "A panel with a small slot".to_string(),
+            id: "lab_panel".to_string(),
+            interacted: false,
+            interaction_description: "The pendant clicks into the panel".to_string(),
+            name: "panel".to_string(),
+            prerequisite_item: "pendant".to_string(),
+            ..Default::default()
+        };
+
+        let mut game_state = create_base_game_state();
+        game_state.rooms[0].interactables = vec![stone, panel];
+        game_state.rooms[0].exits[0].interactable_id = "lab_panel".to_string();
+        game_state.rooms[0].exits[0].locked = true;
+        game_state.inventory.get_mut("helmet").unwrap().location = ItemState::Inventory;
+
+        game_state
+    }
+
+    #[test]
+    fn test_use_item_on_target() {
+        let game_state = create_two_lock_game_state();
+
+        let mismatched_state = update(game_state.clone(), "use the helmet on the control panel".to_string());
+
+        assert_eq!(mismatched_state.sys_message, "The helmet does not seem to work on the panel");
+        assert!(mismatched_state.rooms[0].exits[0].is_locked());
+
+        let unlocked_state = update(game_state.clone(), "use pendant on panel".to_string());
+
+        assert_eq!(unlocked_state.sys_message, "The pendant clicks into the panel");
+        assert!(!unlocked_state.rooms[0].exits[0].is_locked());
+
+        let exit_state = update(game_state, "use pendant on south".to_string());
+
+        assert!(!exit_state.rooms[0].exits[0].is_locked());
+    }
+
+    #[test]
+    fn test_use_without_exit_does_not_unlock() {
+        let game_state = create_two_lock_game_state();
+
+        let after_state = update(game_state, "use helmet on stone".to_string());
+
+        assert_eq!(after_state.sys_message, "The stone rolls onto the floor");
+        assert!(after_state.rooms[0].exits[0].is_locked());
+        assert!(after_state.rooms[0].interactables[0].is_interacted());
+    }
+
+    #[test]
+    fn test_equip_reveals_descriptions() {
+        let mut game_state = start_game();
+        game_state.inventory.get_mut("helmet").unwrap().move_to_room(0);
+        let game_state = update(game_state, "grab helmet".to_string());
+
+        let before_state = update(game_state.clone(), "examine door".to_string());
+
+        assert!(!before_state.sys_message.contains("interlocking gears"));
+
+        let equipped_state = update(game_state, "put on the helmet".to_string());
+
+        assert_eq!(equipped_state.sys_message, "You put on the helmet");
+        assert!(equipped_state.inventory.get("helmet").unwrap().is_equipped());
+
+        let after_state = update(equipped_state, "examine door".to_string());
+
+        assert!(after_state.sys_message.contains("interlocking gears"));
+
+        let removed_state = update(after_state, "take off the helmet".to_string());
+
+        assert_eq!(removed_state.sys_message, "You take off the helmet");
+        assert!(removed_state.inventory.get("helmet").unwrap().is_in_inventory());
+    }
+
+    #[test]
+    fn test_equip_slot_taken() {
+        let mut game_state = create_base_game_state();
+        for name in &["helmet", "buster"] {
+            let item = game_state.inventory.get_mut(*name).unwrap();
+            item.location = ItemState::Inventory;
+            item.slot = Some(Slot::Head);
+        }
+
+        let game_state = update(game_state, "wear helmet".to_string());
+        let game_state = update(game_state, "wear buster".to_string());
+
+        assert_eq!(game_state.sys_message, "You are already wearing the helmet on your head");
+        assert!(!game_state.inventory.get("buster").unwrap().is_equipped());
+    }
+
+    #[test]
+    fn test_equip_requires_slot() {
+        let game_state = update(create_base_game_state(), "equip pendant".to_string());
+
+        assert_eq!(game_state.sys_message, "The pendant is not something you can wear");
+    }
+
+    #[test]
+    fn test_final_choice_endings() {
+        let mut game_state = start_game();
+        for input in &["use pendant on door", "go south", "go east", "go south", "go south"] {
+            game_state = update(game_state, input.to_string());
+        }
+
+        assert_eq!(game_state.current_room_idx, game_state.find_room("dungeon_exit").unwrap());
+
+        let asked_state = update(game_state.clone(), "pull the plug".to_string());
+
+        assert!(asked_state.sys_message.ends_with("Will you pull the plug? (yes/no)"));
+        assert_eq!(asked_state.status, GameStatus::Playing);
+
+        let refused_state = update(asked_state, "no".to_string());
+
+        assert_eq!(refused_state.status, GameStatus::Playing);
+
+        let asked_state = update(refused_state, "pull the plug".to_string());
+        let sleep_state = update(asked_state, "yes".to_string());
+
+        assert_eq!(sleep_state.status, GameStatus::Won("sleep".to_string()));
+        assert!(sleep_state.sys_message.starts_with("You pull. The plug comes free with a spark.\nThe hum"));
+        assert_eq!(sleep_state.ending().unwrap().id, "sleep");
+
+        let vigil_state = update(game_state, "press speaker".to_string());
+
+        assert_eq!(vigil_state.status, GameStatus::Won("vigil".to_string()));
+
+        let over_state = update(vigil_state, "pull plug".to_string());
+
+        assert_eq!(over_state.sys_message, "The game is over.");
+        assert_eq!(over_state.status, GameStatus::Won("vigil".to_string()));
+    }
 }
@@ -0,0 +1,165 @@
+use super::*;
+
+// What the player sees in a room without light. Exits that lead toward a lit room stay visible
+// by the light coming through them
+pub fn describe_darkness(game_state: &GameState, room_idx: usize) -> String {
+    let mut description = "It is pitch dark. You can not see a thing.".to_string();
+
+    let positions: Vec<String> = game_state.rooms[room_idx]
+        .exits
+        .iter()
+        .filter(|x| x.concealment.is_visible() && game_state.is_lit(exit_target(game_state, x)))
+        .map(|x| x.direction.get_position())
+        .collect();
+    if !positions.is_empty() {
+        description.push_str(&format!("\nThere is light {}.", positions.join(" and ")));
+    }
+
+    description
+}
+
+// Handles moving through a dark room toward anything other than a lit room
+pub fn stumble(room_idx: usize, outcome: &mut Outcome) {
+    outcome.error("You stumble around in the dark and can not find the way");
+    outcome.event(GameEvent::Stumbled(room_idx));
+}
+
+// Handles "switch on <item>" and "switch off <item>"
+pub fn switch_light(game_state: &mut GameState, parsed_input: &Input, on: bool, outcome: &mut Outcome) {
+    let key = match &parsed_input.direct_object {
+        Some(Object::Item(key)) => key.clone(),
+        _ => return,
+    };
+
+    let room_idx = game_state.current_room_idx;
+    let was_lit = game_state.is_lit(room_idx);
+    let word = if on { "on" } else { "off" };
+
+    let light = match game_state.inventory.get_mut::<str>(&key).and_then(Item::get_light_mut) {
+        Some(light) => light,
+        None => {
+            outcome.error(&format!("The {} can not be switched {}", key, word));
+            return;
+        }
+    };
+
+    if light.on == on {
+        outcome.error(&format!("The {} is already {}", key, word));
+        return;
+    }
+
+    light.on = on;
+    outcome.narrate(&format!("You switch {} the {}", word, key));
+    outcome.event(if on {
+        GameEvent::LightSwitchedOn(key)
+    } else {
+        GameEvent::LightSwitchedOff(key)
+    });
+
+    if game_state.is_lit(room_idx) != was_lit {
+        outcome.room_description(&game_state.describe_room(room_idx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    // The default world in the dark basement, carrying the flashlight
+    fn create_dark_game_state() -> GameState {
+        let mut game_state = in_room(start_game(), "basement_lab");
+        game_state.inventory.get_mut("flashlight").unwrap().move_to_inventory();
+        game_state
+    }
+
+    #[test]
+    fn test_darkness_hides_the_room() {
+        let game_state = create_dark_game_state();
+        let room_idx = game_state.current_room_idx;
+
+        assert_eq!(
+            game_state.describe_room(room_idx),
+            "It is pitch dark. You can not see a thing.\nThere is light above."
+        );
+        assert!(game_state.items_in_room(room_idx).is_empty());
+        assert_eq!(
+            update(game_state.clone(), "examine table".to_string()).sys_message,
+            "You don't see any table here."
+        );
+        assert_eq!(
+            update(game_state, "search".to_string()).sys_message,
+            "It is too dark to search here"
+        );
+    }
+
+    #[test]
+    fn test_moving_in_the_dark() {
+        let game_state = create_dark_game_state();
+        let room_idx = game_state.current_room_idx;
+
+        let stumbled_state = update(game_state, "go north".to_string());
+
+        assert_eq!(stumbled_state.current_room_idx, room_idx);
+        assert!(stumbled_state
+            .outcome
+            .events
+            .contains(&GameEvent::Stumbled(room_idx)));
+        assert!(stumbled_state.sys_message.starts_with("You stumble around in the dark"));
+
+        let nowhere_state = update(stumbled_state, "go south".to_string());
+
+        assert!(nowhere_state.sys_message.starts_with("You stumble around in the dark"));
+
+        let moved_state = update(nowhere_state, "go up".to_string());
+
+        assert_eq!(moved_state.current_room_idx, moved_state.find_room("north_door_room").unwrap());
+    }
+
+    #[test]
+    fn test_switching_on_a_light() {
+        let game_state = update(create_dark_game_state(), "turn on the flashlight".to_string());
+        let room_idx = game_state.current_room_idx;
+
+        assert!(game_state.is_lit(room_idx));
+        assert_eq!(
+            game_state.outcome.blocks.last(),
+            Some(&OutputBlock::RoomDescription(game_state.describe_room(room_idx)))
+        );
+        assert!(game_state.describe_room(room_idx).contains("steel table"));
+        assert_eq!(
+            update(game_state.clone(), "light flashlight".to_string()).sys_message,
+            "The flashlight is already on"
+        );
+
+        let moved_state = update(game_state, "go north".to_string());
+
+        assert_eq!(moved_state.current_room_idx, moved_state.find_room("storage").unwrap());
+
+        let dropped_state = update(moved_state, "drop flashlight".to_string());
+        let returned_state = update(dropped_state, "go south".to_string());
+
+        assert!(!returned_state.is_lit(room_idx));
+        assert!(returned_state.is_lit(returned_state.find_room("storage").unwrap()));
+        assert_eq!(
+            returned_state.describe_room(room_idx),
+            "It is pitch dark. You can not see a thing.\nThere is light to the north and above."
+        );
+    }
+
+    #[test]
+    fn test_switching_off_a_light() {
+        let game_state = update(create_dark_game_state(), "switch on flashlight".to_string());
+
+        let dark_state = update(game_state, "switch off flashlight".to_string());
+
+        assert_eq!(
+            dark_state.sys_message,
+            "You switch off the flashlight\nIt is pitch dark. You can not see a thing.\nThere is light above."
+        );
+        assert_eq!(
+            update(dark_state, "turn off pendant".to_string()).sys_message,
+            "The pendant can not be switched off"
+        );
+    }
+}
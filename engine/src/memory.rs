@@ -0,0 +1,172 @@
+use serde_derive::{Deserialize, Serialize};
+
+use super::*;
+
+// Commands that work in every memory
+static LOOK_COMMANDS: &[&str] = &["look", "look around"];
+static LEAVE_COMMANDS: &[&str] = &["leave", "return", "wake up"];
+
+/// A scene from the past that the player can step into, such as one rendered by the helmet.
+/// While a memory plays, only its own commands can be used.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Memory {
+    pub id: String,
+    // Shown when the memory begins and when the player looks around in it
+    pub description: String,
+    #[serde(default)]
+    pub commands: Vec<SceneCommand>,
+    // Shown when the player returns to the present
+    #[serde(default)]
+    pub closing: String,
+}
+
+/// Something the player can do inside a memory.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SceneCommand {
+    // The words that run the command, such as "watch albert"
+    pub command: String,
+    pub text: String,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    // Whether the memory ends after the command
+    #[serde(default)]
+    pub ends: bool,
+}
+
+// Starts playing a memory. Memories count as seen as soon as they begin
+pub fn enter_memory(game_state: &mut GameState, id: &str, outcome: &mut Outcome) {
+    let description = match game_state.memories.iter().find(|x| x.id == id) {
+        Some(memory) => memory.description.clone(),
+        None => return,
+    };
+
+    game_state.scene = Some(id.to_string());
+    game_state.memories_seen.insert(id.to_string());
+    outcome.room_description(&render(&description, game_state));
+    outcome.event(GameEvent::MemoryEntered(id.to_string()));
+}
+
+fn leave_memory(game_state: &mut GameState, memory: &Memory, outcome: &mut Outcome) {
+    game_state.scene = None;
+
+    if !memory.closing.is_empty() {
+        outcome.narrate(&render(&memory.closing, game_state));
+    }
+    outcome.room_description(&game_state.describe_room(game_state.current_room_idx));
+    outcome.event(GameEvent::MemoryLeft(memory.id.clone()));
+}
+
+// Reads the input as one of the commands of the memory being played. Anything else only reminds
+// the player of what they can do
+pub fn play_scene(game_state: &mut GameState, input: &str, outcome: &mut Outcome) {
+    let memory = match game_state
+        .scene
+        .as_ref()
+        .and_then(|id| game_state.memories.iter().find(|x| &x.id == id))
+    {
+        Some(memory) => memory.clone(),
+        None => {
+            game_state.scene = None;
+            return;
+        }
+    };
+
+    let command = tokenize(input).join(" ");
+
+    if LOOK_COMMANDS.contains(&command.as_str()) {
+        outcome.room_description(&render(&memory.description, game_state));
+        return;
+    }
+
+    if LEAVE_COMMANDS.contains(&command.as_str()) {
+        leave_memory(game_state, &memory, outcome);
+        return;
+    }
+
+    match memory
+        .commands
+        .iter()
+        .find(|x| tokenize(&x.command).join(" ") == command)
+    {
+        Some(scene_command) => {
+            outcome.narrate(&render(&scene_command.text, game_state));
+            for action in &scene_command.actions {
+                run_action(game_state, action, outcome);
+            }
+            if scene_command.ends {
+                leave_memory(game_state, &memory, outcome);
+            }
+        }
+        None => {
+            let mut commands: Vec<&str> = memory.commands.iter().map(|x| x.command.as_str()).collect();
+            commands.push(LOOK_COMMANDS[0]);
+            commands.push(LEAVE_COMMANDS[0]);
+            outcome.error(&format!(
+                "That is not something you can do in a memory. You can: {}",
+                commands.join(", ")
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn test_memory_needs_the_helmet() {
+        let game_state = update(start_game(), "touch the insignia".to_string());
+
+        assert_eq!(game_state.sys_message, "You currently can not interact with insignia");
+        assert!(game_state.scene.is_none());
+    }
+
+    #[test]
+    fn test_memory_limits_commands() {
+        let game_state = update(game_wearing_helmet(), "touch the insignia".to_string());
+
+        assert_eq!(game_state.scene, Some("albert_and_robot".to_string()));
+        assert!(game_state.memories_seen.contains("albert_and_robot"));
+        assert_eq!(
+            game_state.outcome.events,
+            vec![
+                GameEvent::InteractableTriggered("insignia".to_string()),
+                GameEvent::MemoryEntered("albert_and_robot".to_string())
+            ]
+        );
+
+        let blocked_state = update(game_state, "go south".to_string());
+
+        assert_eq!(
+            blocked_state.sys_message,
+            "That is not something you can do in a memory. You can: watch, listen, look, leave"
+        );
+
+        let watched_state = update(blocked_state, "watch".to_string());
+
+        assert!(watched_state.flags.contains("saw_the_chip"));
+        assert!(watched_state.scene.is_some());
+    }
+
+    #[test]
+    fn test_return_to_the_present() {
+        let game_state = update(game_wearing_helmet(), "touch the insignia".to_string());
+
+        let returned_state = update(game_state, "wake up".to_string());
+
+        assert!(returned_state.scene.is_none());
+        assert_eq!(
+            returned_state.outcome.events,
+            vec![GameEvent::MemoryLeft("albert_and_robot".to_string())]
+        );
+        assert_eq!(
+            returned_state.outcome.blocks.last(),
+            Some(&OutputBlock::RoomDescription(returned_state.describe_room(0)))
+        );
+
+        let restored_state = GameState::restore(&returned_state.save()).unwrap();
+
+        assert!(restored_state.memories_seen.contains("albert_and_robot"));
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use super::*;
+
+/// A character the player can meet and talk to.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Npc {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub adjectives: Vec<String>,
+    pub description: String,
+    // The room the character is in
+    pub room_idx: usize,
+    // Whether the character moves along with the player
+    #[serde(default)]
+    pub follows: bool,
+    #[serde(default)]
+    pub dialogue: Dialogue,
+    #[serde(default)]
+    pub orders: Vec<Order>,
+}
+
+impl Examine for Npc {
+    fn examine(&self) -> &str {
+        &self.description
+    }
+}
+
+/// What a character can say, as a tree of nodes connected by the options the player picks.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Dialogue {
+    // The node a conversation starts at
+    pub start: String,
+    #[serde(default)]
+    pub nodes: BTreeMap<String, DialogueNode>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DialogueNode {
+    // What the character says
+    pub text: String,
+    #[serde(default)]
+    pub options: Vec<DialogueOption>,
+}
+
+/// Something the player can say in reply. Options whose conditions do not hold are not offered,
+/// and the conversation ends after an option without a `next` node.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DialogueOption {
+    pub text: String,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+/// Something a character can be told to do, as in "robot, climb the wall".
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Order {
+    // The words of the order, such as "climb wall"
+    pub command: String,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    // Shown when the conditions do not hold
+    #[serde(default)]
+    pub refusal: String,
+}
+
+// Orders that every character understands
+static FOLLOW_ORDERS: &[&str] = &["come", "follow", "follow me"];
+static STAY_ORDERS: &[&str] = &["stay", "stay here", "stop", "wait", "wait here"];
+
+// Handles "<npc>, <order>" and "tell <npc> to <order>"
+pub fn give_order(game_state: &mut GameState, id: &str, order: &str, outcome: &mut Outcome) {
+    let npc = match game_state.npcs.iter_mut().find(|x| x.id == id) {
+        Some(npc) => npc,
+        None => return,
+    };
+
+    if FOLLOW_ORDERS.contains(&order) {
+        if npc.follows {
+            outcome.error(&format!("The {} is already following you", npc.name));
+        } else {
+            npc.follows = true;
+            outcome.narrate(&format!("The {} will follow you", npc.name));
+        }
+        return;
+    }
+
+    if STAY_ORDERS.contains(&order) {
+        if npc.follows {
+            npc.follows = false;
+            outcome.narrate(&format!("The {} stays where it is", npc.name));
+        } else {
+            outcome.error(&format!("The {} is not following you", npc.name));
+        }
+        return;
+    }
+
+    let name = npc.name.clone();
+    let order = match npc.orders.iter().find(|x| tokenize(&x.command).join(" ") == order) {
+        Some(order) => order.clone(),
+        None => {
+            outcome.error(&format!("The {} does not know how to {}", name, order));
+            return;
+        }
+    };
+
+    if order.conditions.iter().all(|x| x.holds(game_state)) {
+        for action in &order.actions {
+            run_action(game_state, action, outcome);
+        }
+    } else if order.refusal.is_empty() {
+        outcome.error(&format!("The {} can not do that right now", name));
+    } else {
+        outcome.error(&render(&order.refusal, game_state));
+    }
+}
+
+// Handles "talk to <npc>"
+pub fn talk_to(game_state: &mut GameState, id: &str, outcome: &mut Outcome) {
+    let (name, start) = match game_state.npcs.iter().find(|x| x.id == id) {
+        Some(npc) => (npc.name.clone(), npc.dialogue.start.clone()),
+        None => return,
+    };
+
+    outcome.event(GameEvent::TalkedTo(id.to_string()));
+
+    if start.is_empty() {
+        outcome.narrate(&format!("The {} has nothing to say", name));
+    } else {
+        say(game_state, id, &start, outcome);
+    }
+}
+
+// Shows what a character says at a dialogue node and asks the player which option to reply with
+pub fn say(game_state: &mut GameState, id: &str, node_id: &str, outcome: &mut Outcome) {
+    let node = match game_state
+        .npcs
+        .iter()
+        .find(|x| x.id == id)
+        .and_then(|x| x.dialogue.nodes.get(node_id))
+    {
+        Some(node) => node.clone(),
+        None => return,
+    };
+
+    let choices: Vec<Choice> = node
+        .options
+        .iter()
+        .filter(|x| x.conditions.iter().all(|condition| condition.holds(game_state)))
+        .enumerate()
+        .map(|(idx, option)| {
+            let mut actions = option.actions.clone();
+            if let Some(next) = &option.next {
+                actions.push(Action::Talk {
+                    npc: id.to_string(),
+                    node: next.clone(),
+                });
+            }
+
+            Choice {
+                answer: (idx + 1).to_string(),
+                text: option.text.clone(),
+                aliases: vec![],
+                actions,
+            }
+        })
+        .collect();
+
+    if choices.is_empty() {
+        outcome.narrate(&render(&node.text, game_state));
+    } else {
+        let prompt = Prompt {
+            question: node.text,
+            choices,
+        };
+        ask(game_state, &prompt, outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    fn node(text: &str, options: Vec<DialogueOption>) -> DialogueNode {
+        DialogueNode {
+            text: text.to_string(),
+            options,
+        }
+    }
+
+    fn option(text: &str, next: Option<&str>) -> DialogueOption {
+        DialogueOption {
+            text: text.to_string(),
+            next: next.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    // The default world with a guard standing in the starting room
+    fn create_npc_game_state() -> GameState {
+        let mut photograph = option("Do you know this girl?", Some("eda"));
+        photograph.conditions = vec![Condition::HasItem {
+            item: "photograph".to_string(),
+        }];
+        photograph.actions = vec![Action::SetFlag {
+            flag: "asked_about_eda".to_string(),
+        }];
+
+        let mut gift = option("Thank you", None);
+        gift.actions = vec![Action::MoveItem {
+            item: "disk".to_string(),
+            to: ItemDestination::Inventory,
+        }];
+
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            "start".to_string(),
+            node("\"Halt,\" says the guard.", vec![photograph, option("Goodbye", None)]),
+        );
+        nodes.insert("eda".to_string(), node("\"Take this.\"", vec![gift]));
+
+        GameState {
+            npcs: vec![Npc {
+                adjectives: vec!["old".to_string()],
+                description: "An old guard leaning on a spear".to_string(),
+                dialogue: Dialogue {
+                    start: "start".to_string(),
+                    nodes,
+                },
+                ..npc("guard", 0)
+            }],
+            ..start_game()
+        }
+    }
+
+    #[test]
+    fn test_talk_to_npc() {
+        let game_state = update(create_npc_game_state(), "talk to the old guard".to_string());
+
+        assert_eq!(game_state.sys_message, "\"Halt,\" says the guard.\n1. Goodbye");
+        assert_eq!(
+            game_state.outcome.events,
+            vec![GameEvent::TalkedTo("guard".to_string())]
+        );
+
+        let ended_state = update(game_state, "1".to_string());
+
+        assert!(ended_state.prompt.is_none());
+    }
+
+    #[test]
+    fn test_options_gated_on_inventory() {
+        let mut game_state = create_npc_game_state();
+        game_state.inventory.get_mut("photograph").unwrap().move_to_inventory();
+
+        let talking_state = update(game_state, "talk to guard".to_string());
+
+        assert_eq!(
+            talking_state.sys_message,
+            "\"Halt,\" says the guard.\n1. Do you know this girl?\n2. Goodbye"
+        );
+
+        let next_state = update(talking_state, "1".to_string());
+
+        assert_eq!(next_state.sys_message, "\"Take this.\"\n1. Thank you");
+        assert!(next_state.flags.contains("asked_about_eda"));
+
+        let gift_state = update(next_state, "1".to_string());
+
+        assert!(gift_state.inventory.get("disk").unwrap().is_in_inventory());
+        assert!(gift_state.prompt.is_none());
+    }
+
+    #[test]
+    fn test_npc_in_room() {
+        let game_state = create_npc_game_state();
+
+        let examined_state = update(game_state.clone(), "examine guard".to_string());
+
+        assert_eq!(examined_state.sys_message, "An old guard leaning on a spear");
+        assert!(game_state.describe_room(0).ends_with("\nAlso here: guard"));
+
+        let mut elsewhere_state = game_state;
+        elsewhere_state.npcs[0].room_idx = 2;
+
+        let missing_state = update(elsewhere_state, "talk to guard".to_string());
+
+        assert_eq!(missing_state.sys_message, "You don't see any guard here.");
+    }
+
+    #[test]
+    fn test_follower_moves_with_player() {
+        let game_state = update(start_game(), "robot, follow me".to_string());
+
+        assert_eq!(game_state.sys_message, "The robot will follow you");
+
+        let game_state = update(game_state, "use pendant on door".to_string());
+        let moved_state = update(game_state, "go south".to_string());
+
+        assert_eq!(moved_state.npcs[0].room_idx, 2);
+        assert!(moved_state.sys_message.ends_with("\nAlso here: robot"));
+
+        let stayed_state = update(moved_state, "tell the robot to wait".to_string());
+
+        assert_eq!(stayed_state.sys_message, "The robot stays where it is");
+
+        let left_state = update(stayed_state, "go north".to_string());
+
+        assert_eq!(left_state.npcs[0].room_idx, 2);
+    }
+
+    #[test]
+    fn test_authored_orders() {
+        let game_state = update(start_game(), "robot, climb the wall".to_string());
+
+        assert!(game_state.inventory.get("helmet").unwrap().is_in_room(0));
+        assert!(game_state.flags.contains("robot_climbed_wall"));
+
+        let again_state = update(game_state, "robot, climb wall".to_string());
+
+        assert_eq!(again_state.sys_message, "The robot has already been up there.");
+
+        let unknown_state = update(again_state, "robot, dance".to_string());
+
+        assert_eq!(unknown_state.sys_message, "The robot does not know how to dance");
+    }
+}
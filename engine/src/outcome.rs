@@ -0,0 +1,153 @@
+use super::*;
+
+/// A piece of output produced by a turn, tagged with what kind of text it is so that frontends
+/// can present it differently.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputBlock {
+    RoomDescription(String),
+    ItemList { heading: String, items: Vec<String> },
+    Error(String),
+    Narration(String),
+}
+
+impl OutputBlock {
+    pub fn text(&self) -> String {
+        match self {
+            OutputBlock::RoomDescription(text)
+            | OutputBlock::Error(text)
+            | OutputBlock::Narration(text) => text.clone(),
+            OutputBlock::ItemList { heading, items } => {
+                let mut lines = vec![heading.clone()];
+                lines.extend(items.iter().cloned());
+                lines.join("\n")
+            }
+        }
+    }
+}
+
+/// Something that happened in the world during a turn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    ContainerClosed(String),
+    ContainerLocked(String),
+    ContainerOpened(String),
+    ContainerUnlocked(String),
+    EraChanged(String),
+    ExitUnlocked { room_idx: usize, direction: Direction },
+    FlagSet(String),
+    InteractableTriggered(String),
+    ItemCrafted(String),
+    ItemDropped(String),
+    ItemEquipped(String),
+    ItemStored { item: String, container: String },
+    ItemTaken(String),
+    ItemUnequipped(String),
+    ItemUsed(String),
+    LightSwitchedOff(String),
+    LightSwitchedOn(String),
+    MemoryEntered(String),
+    MemoryLeft(String),
+    RoomEntered(usize),
+    // The player tried to move through a dark room without a light
+    Stumbled(usize),
+    TalkedTo(String),
+}
+
+/// The result of a single turn: the text to show the player and the events that occurred.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcome {
+    pub blocks: Vec<OutputBlock>,
+    pub events: Vec<GameEvent>,
+}
+
+impl Outcome {
+    pub fn room_description(&mut self, text: &str) {
+        self.blocks.push(OutputBlock::RoomDescription(text.to_string()));
+    }
+
+    pub fn item_list(&mut self, heading: &str, items: Vec<String>) {
+        self.blocks.push(OutputBlock::ItemList {
+            heading: heading.to_string(),
+            items,
+        });
+    }
+
+    pub fn error(&mut self, text: &str) {
+        self.blocks.push(OutputBlock::Error(text.to_string()));
+    }
+
+    pub fn narrate(&mut self, text: &str) {
+        self.blocks.push(OutputBlock::Narration(text.to_string()));
+    }
+
+    pub fn event(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    /// The plain text form of the outcome, used to fill in GameState.sys_message.
+    pub fn message(&self) -> String {
+        self.blocks
+            .iter()
+            .map(OutputBlock::text)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_joins_blocks() {
+        let mut outcome = Outcome::default();
+        outcome.narrate("The stone rolls onto the floor");
+        outcome.item_list(
+            "Your inventory:",
+            vec!["helmet: a blue helmet".to_string()],
+        );
+
+        assert_eq!(
+            outcome.message(),
+            "The stone rolls onto the floor\nYour inventory:\nhelmet: a blue helmet"
+        );
+    }
+
+    #[test]
+    fn test_move_reports_room_entered() {
+        let game_state = update(start_game(), "use pendant".to_string());
+
+        assert_eq!(
+            game_state.outcome.events,
+            vec![
+                GameEvent::ExitUnlocked {
+                    room_idx: 0,
+                    direction: Direction::S
+                },
+                GameEvent::InteractableTriggered("lab_entrance".to_string()),
+                GameEvent::ItemUsed("pendant".to_string()),
+            ]
+        );
+
+        let game_state = update(game_state, "go south".to_string());
+
+        assert_eq!(game_state.outcome.events, vec![GameEvent::RoomEntered(2)]);
+        assert_eq!(
+            game_state.outcome.blocks,
+            vec![OutputBlock::RoomDescription(
+                "You find yourself in a room. There is a door to the north, a door to the east and a dark stairway leading down. A heavy desk stands against the wall and a stopped clock hangs above it."
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unknown_command_is_an_error() {
+        let game_state = update(start_game(), "dance".to_string());
+
+        assert_eq!(
+            game_state.outcome.blocks,
+            vec![OutputBlock::Error("dance is not a legal command".to_string())]
+        );
+    }
+}
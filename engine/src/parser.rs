@@ -0,0 +1,726 @@
+use std::fmt;
+
+use super::*;
+
+/// Why a line of player input could not be turned into a command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    AmbiguousObject(String),
+    EmptyInput,
+    MissingObject(String),
+    NotApplicable { verb: String, object: String },
+    UnknownObject(String),
+    UnknownVerb(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::AmbiguousObject(object) => write!(
+                f,
+                "There is more than one {} here. Please be more specific.",
+                object
+            ),
+            ParseError::EmptyInput => write!(f, "Please enter a command."),
+            ParseError::MissingObject(verb) => write!(f, "What do you want to {}?", verb),
+            ParseError::NotApplicable { verb, object } => {
+                write!(f, "You can not {} the {}.", verb, object)
+            }
+            ParseError::UnknownObject(object) => write!(f, "You don't see any {} here.", object),
+            ParseError::UnknownVerb(verb) => write!(f, "{} is not a legal command", verb),
+        }
+    }
+}
+
+// Words that carry no meaning for the parser
+static ARTICLES: &[&str] = &["a", "an", "my", "some", "the"];
+
+// Verbs that mean going out when they are used on their own
+static OUT_VERBS: &[&str] = &["exit", "leave"];
+
+// Words that separate the direct object of a command from its indirect object
+static PREPOSITIONS: &[&str] = &[
+    "at", "from", "in", "into", "on", "onto", "through", "to", "using", "with",
+];
+
+/// Something in the world that a command refers to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Object {
+    Direction(Direction),
+    // The id of an interactable in the current room
+    Interactable(String),
+    Inventory,
+    // The key of an item in GameState.inventory
+    Item(String),
+    // The id of a character in the current room
+    Npc(String),
+}
+
+#[derive(Debug, Default)]
+pub struct Input {
+    pub intent: Intent,
+    pub verb: String,
+    pub direct_object: Option<Object>,
+    // The direct object as the player typed it, without articles
+    pub object_noun: String,
+    pub preposition: Option<String>,
+    pub indirect_object: Option<Object>,
+    pub indirect_noun: String,
+    // What a character is told to do, as in "robot, climb the wall"
+    pub order: String,
+}
+
+// Whether a command with this intent needs something to act on
+fn requires_object(intent: &Intent) -> bool {
+    matches!(
+        intent,
+        Intent::CLOSE
+            | Intent::COMBINE
+            | Intent::DROP
+            | Intent::EQUIP
+            | Intent::UNEQUIP
+            | Intent::EXAMINE
+            | Intent::INTERACT
+            | Intent::INVENTORY
+            | Intent::LOCK
+            | Intent::MOVEMENT
+            | Intent::OPEN
+            | Intent::ORDER
+            | Intent::PUT
+            | Intent::SWITCH_OFF
+            | Intent::SWITCH_ON
+            | Intent::TALK
+            | Intent::UNLOCK
+            | Intent::USE
+    )
+}
+
+// Whether the verb can be used on the kind of object the player named
+fn is_applicable(intent: &Intent, object: &Object) -> bool {
+    match (intent, object) {
+        (Intent::EXAMINE, Object::Interactable(_)) | (Intent::EXAMINE, Object::Item(_)) => true,
+        (Intent::EXAMINE, Object::Npc(_)) | (Intent::TALK, Object::Npc(_)) => true,
+        (Intent::ORDER, Object::Npc(_)) => true,
+        (Intent::INTERACT, Object::Interactable(_)) => true,
+        (Intent::INVENTORY, Object::Item(_)) | (Intent::USE, Object::Item(_)) => true,
+        (Intent::DROP, Object::Item(_)) | (Intent::PUT, Object::Item(_)) => true,
+        (Intent::OPEN, Object::Item(_)) | (Intent::CLOSE, Object::Item(_)) => true,
+        (Intent::LOCK, Object::Item(_)) | (Intent::UNLOCK, Object::Item(_)) => true,
+        (Intent::SEARCH, Object::Item(_)) | (Intent::COMBINE, Object::Item(_)) => true,
+        // Searching an interactable searches the room around it
+        (Intent::SEARCH, Object::Interactable(_)) => true,
+        (Intent::SEARCH, _) => false,
+        (Intent::EQUIP, Object::Item(_)) | (Intent::UNEQUIP, Object::Item(_)) => true,
+        (Intent::SWITCH_ON, Object::Item(_)) | (Intent::SWITCH_OFF, Object::Item(_)) => true,
+        (Intent::LIST_INVENTORY, Object::Inventory) => true,
+        (Intent::MOVEMENT, Object::Direction(_)) => true,
+        (Intent::LIST_INVENTORY, _) => false,
+        (intent, _) => !requires_object(intent),
+    }
+}
+
+/// Splits player input into lowercase words, dropping punctuation and articles.
+pub fn tokenize(input: &str) -> Vec<String> {
+    input
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty() && !ARTICLES.contains(&word.as_str()))
+        .collect()
+}
+
+// Finds the longest verb phrase ("pick up", "look at") at the start of the words
+fn split_verb(words: &[String]) -> Option<(String, &[String])> {
+    (1..=words.len().min(2)).rev().find_map(|len| {
+        let phrase = words[..len].join(" ");
+        if is_legal_command(&phrase) {
+            Some((phrase, &words[len..]))
+        } else {
+            None
+        }
+    })
+}
+
+// Whether a noun phrase such as "rusty pendant" names something called `name` described by
+// `adjectives`. The phrase has to contain the last word of the name.
+fn matches_phrase(words: &[String], name: &str, adjectives: &[String]) -> bool {
+    let name_words: Vec<&str> = name.split_whitespace().collect();
+
+    match name_words.last() {
+        Some(head) => {
+            words.iter().any(|word| word == head)
+                && words
+                    .iter()
+                    .all(|word| name_words.contains(&word.as_str()) || adjectives.contains(word))
+        }
+        None => false,
+    }
+}
+
+fn resolve_object(words: &[String], game_state: &GameState) -> Result<Object, ParseError> {
+    let phrase = words.join(" ");
+
+    if phrase == "inventory" {
+        return Ok(Object::Inventory);
+    }
+
+    if is_direction(&phrase) {
+        return Ok(Object::Direction(text_to_direction(&phrase).unwrap_or_default()));
+    }
+
+    let room = &game_state.rooms[game_state.current_room_idx];
+    // Nothing in a dark room can be seen, although the player can still feel what they carry
+    let is_lit = game_state.is_lit(game_state.current_room_idx);
+    // Only items the player carries or can see in the room can be referred to
+    let mut candidates: Vec<Object> = game_state
+        .inventory
+        .iter()
+        .filter(|(key, _)| game_state.is_reachable(key))
+        .filter(|(_, item)| matches_phrase(words, &item.name, &item.adjectives))
+        .map(|(key, _)| Object::Item(key.clone()))
+        .collect();
+    candidates.extend(
+        room.interactables
+            .iter()
+            .filter(|x| is_lit && x.concealment.is_visible())
+            .filter(|x| matches_phrase(words, &x.name, &x.adjectives))
+            .map(|x| Object::Interactable(x.id.clone())),
+    );
+    candidates.extend(
+        game_state
+            .npcs
+            .iter()
+            .filter(|x| is_lit && x.room_idx == game_state.current_room_idx)
+            .filter(|x| matches_phrase(words, &x.name, &x.adjectives))
+            .map(|x| Object::Npc(x.id.clone())),
+    );
+
+    match candidates.len() {
+        0 => Err(ParseError::UnknownObject(phrase)),
+        1 => Ok(candidates.remove(0)),
+        _ => Err(ParseError::AmbiguousObject(phrase)),
+    }
+}
+
+// Finds the exit that a noun phrase names, such as "steel door" or "down the ladder". Exits in a
+// dark room can only be found when they lead toward light
+fn resolve_exit(words: &[String], game_state: &GameState) -> Result<Option<Direction>, ParseError> {
+    let room_idx = game_state.current_room_idx;
+    let is_lit = game_state.is_lit(room_idx);
+
+    let mut directions: Vec<Direction> = game_state.rooms[room_idx]
+        .exits
+        .iter()
+        .filter(|x| !x.name.is_empty() && x.concealment.is_visible())
+        .filter(|x| is_lit || game_state.is_lit(exit_target(game_state, x)))
+        .filter(|x| {
+            matches_phrase(words, &x.name, &x.adjectives)
+                || (words.len() > 1
+                    && text_to_direction(&words[0]).as_ref() == Some(&x.direction)
+                    && matches_phrase(&words[1..], &x.name, &x.adjectives))
+        })
+        .map(|x| x.direction.clone())
+        .collect();
+
+    match directions.len() {
+        0 => Ok(None),
+        1 => Ok(Some(directions.remove(0))),
+        _ => Err(ParseError::AmbiguousObject(words.join(" "))),
+    }
+}
+
+// Whether the player can see an exit in the given direction
+fn has_exit(game_state: &GameState, direction: &Direction) -> bool {
+    game_state.rooms[game_state.current_room_idx]
+        .exits
+        .iter()
+        .any(|x| &x.direction == direction && x.concealment.is_visible())
+}
+
+// Moving in a direction, for "north" on its own and for "go in", where "in" would otherwise be
+// read as a preposition
+fn movement(verb: &str, words: &[String]) -> Option<Input> {
+    let phrase = words.join(" ");
+
+    text_to_direction(&phrase).map(|direction| Input {
+        intent: Intent::MOVEMENT,
+        verb: verb.to_string(),
+        direct_object: Some(Object::Direction(direction)),
+        object_noun: phrase,
+        ..Default::default()
+    })
+}
+
+// Reads "robot, climb the wall" as an order to a character in the room. Input that does not start
+// by addressing a character is left to the rest of the parser
+fn parse_address(input: &str, game_state: &GameState) -> Option<Result<Input, ParseError>> {
+    let comma = input.find(',')?;
+    let actor_words = tokenize(&input[..comma]);
+
+    let id = match resolve_object(&actor_words, game_state) {
+        Ok(Object::Npc(id)) => id,
+        _ => return None,
+    };
+
+    let order = tokenize(&input[comma + 1..]);
+    if order.is_empty() {
+        return Some(Err(ParseError::MissingObject(format!(
+            "tell the {} to do",
+            actor_words.join(" ")
+        ))));
+    }
+
+    Some(Ok(Input {
+        intent: Intent::ORDER,
+        verb: "tell".to_string(),
+        direct_object: Some(Object::Npc(id)),
+        object_noun: actor_words.join(" "),
+        order: order.join(" "),
+        ..Default::default()
+    }))
+}
+
+/// Turns a line of player input into a command against the current room and inventory.
+///
+/// Commands take the form `verb [direct object] [preposition indirect object]`, such as
+/// "use the rusty pendant on the door". Verbs can be more than one word ("pick up") and objects
+/// can be named with any of their adjectives.
+pub fn parse_input(input: &str, game_state: &GameState) -> Result<Input, ParseError> {
+    if let Some(parsed_input) = parse_address(input, game_state) {
+        return parsed_input;
+    }
+
+    let words = tokenize(input);
+
+    if words.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+
+    if let Some(parsed_input) = movement("go", &words) {
+        return Ok(parsed_input);
+    }
+
+    let (verb, rest) = match split_verb(&words) {
+        Some(verb_and_rest) => verb_and_rest,
+        // If the command is not valid, we do not need to parse the rest of the string input
+        None => return Err(ParseError::UnknownVerb(words[0].clone())),
+    };
+
+    let mut parsed_input = Input {
+        intent: determine_intent(&verb).unwrap_or_default(),
+        verb,
+        ..Default::default()
+    };
+
+    if parsed_input.intent == Intent::MOVEMENT {
+        if let Some(movement) = movement(&parsed_input.verb, rest) {
+            return Ok(movement);
+        }
+    }
+
+    let (mut direct_words, mut indirect_words) =
+        match rest.iter().position(|word| PREPOSITIONS.contains(&word.as_str())) {
+            Some(pos) => {
+                parsed_input.preposition = Some(rest[pos].clone());
+                (&rest[..pos], &rest[pos + 1..])
+            }
+            None => (rest, &rest[rest.len()..]),
+        };
+
+    // "go to the south" has no direct object, so the object of the preposition stands in for it
+    if direct_words.is_empty() {
+        direct_words = indirect_words;
+        indirect_words = &rest[rest.len()..];
+        parsed_input.preposition = None;
+    }
+
+    if direct_words.is_empty() {
+        // "exit" and "leave" take the player out of a room that has a way out
+        if OUT_VERBS.contains(&parsed_input.verb.as_str()) && has_exit(game_state, &Direction::OUT) {
+            return Ok(Input {
+                direct_object: Some(Object::Direction(Direction::OUT)),
+                ..parsed_input
+            });
+        }
+
+        if requires_object(&parsed_input.intent) {
+            return Err(ParseError::MissingObject(parsed_input.verb));
+        }
+
+        return Ok(parsed_input);
+    }
+
+    // Exits can be named, as in "climb the ladder" or "go through the steel door"
+    if parsed_input.intent == Intent::MOVEMENT {
+        if let Some(direction) = resolve_exit(direct_words, game_state)? {
+            parsed_input.direct_object = Some(Object::Direction(direction));
+            parsed_input.object_noun = direct_words.join(" ");
+            return Ok(parsed_input);
+        }
+    }
+
+    let direct_object = resolve_object(direct_words, game_state)?;
+    parsed_input.object_noun = direct_words.join(" ");
+
+    if !is_applicable(&parsed_input.intent, &direct_object) {
+        return Err(ParseError::NotApplicable {
+            verb: parsed_input.verb,
+            object: parsed_input.object_noun,
+        });
+    }
+
+    parsed_input.direct_object = Some(direct_object);
+
+    // "tell the robot to climb the wall" passes the rest of the command on to the character
+    if parsed_input.intent == Intent::ORDER {
+        if indirect_words.is_empty() {
+            return Err(ParseError::MissingObject(format!(
+                "tell the {} to do",
+                parsed_input.object_noun
+            )));
+        }
+        parsed_input.order = indirect_words.join(" ");
+        return Ok(parsed_input);
+    }
+
+    if !indirect_words.is_empty() {
+        parsed_input.indirect_object = Some(resolve_object(indirect_words, game_state)?);
+        parsed_input.indirect_noun = indirect_words.join(" ");
+    } else if parsed_input.preposition.is_some() {
+        return Err(ParseError::MissingObject(format!(
+            "{} the {} {}",
+            parsed_input.verb,
+            parsed_input.object_noun,
+            parsed_input.preposition.unwrap_or_default()
+        )));
+    }
+
+    Ok(parsed_input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // The default world with the helmet lying in the starting room
+    fn helmet_game() -> GameState {
+        let mut game_state = start_game();
+        game_state.inventory.get_mut("helmet").unwrap().move_to_room(0);
+        game_state
+    }
+
+    fn parse_error(input: &str) -> ParseError {
+        parse_input(input, &helmet_game()).unwrap_err()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_error("   "), ParseError::EmptyInput);
+        assert_eq!(
+            parse_error("dance"),
+            ParseError::UnknownVerb("dance".to_string())
+        );
+        assert_eq!(
+            parse_error("examine"),
+            ParseError::MissingObject("examine".to_string())
+        );
+        assert_eq!(
+            parse_error("examine face"),
+            ParseError::UnknownObject("face".to_string())
+        );
+        assert_eq!(
+            parse_error("go helmet"),
+            ParseError::NotApplicable {
+                verb: "go".to_string(),
+                object: "helmet".to_string()
+            }
+        );
+    }
+
+    fn parse(input: &str) -> Input {
+        parse_input(input, &helmet_game()).unwrap()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Use the Rusty pendant, on a door!"),
+            vec!["use", "rusty", "pendant", "on", "door"]
+        );
+    }
+
+    #[test]
+    fn test_phrasal_verbs() {
+        let input = parse("pick up the blue helmet");
+
+        assert_eq!(input.intent, Intent::INVENTORY);
+        assert_eq!(input.direct_object, Some(Object::Item("helmet".to_string())));
+
+        let input = parse("look at the metal door");
+
+        assert_eq!(input.intent, Intent::EXAMINE);
+        assert_eq!(
+            input.direct_object,
+            Some(Object::Interactable("lab_entrance".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_indirect_object() {
+        let input = parse("use the rusty pendant on the door");
+
+        assert_eq!(input.intent, Intent::USE);
+        assert_eq!(input.direct_object, Some(Object::Item("pendant".to_string())));
+        assert_eq!(input.preposition, Some("on".to_string()));
+        assert_eq!(
+            input.indirect_object,
+            Some(Object::Interactable("lab_entrance".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_adjectives_must_match() {
+        assert_eq!(
+            parse_error("examine the shiny pendant"),
+            ParseError::UnknownObject("shiny pendant".to_string())
+        );
+        assert_eq!(
+            parse_error("examine rusty"),
+            ParseError::UnknownObject("rusty".to_string())
+        );
+    }
+
+    #[test]
+    fn test_missing_indirect_object() {
+        assert_eq!(
+            parse_error("use the pendant on"),
+            ParseError::MissingObject("use the pendant on".to_string())
+        );
+    }
+
+    #[test]
+    fn test_every_intent_is_reachable() {
+        // Everything the commands refer to lies within reach of the starting room
+        let mut game_state = helmet_game();
+        game_state.inventory.get_mut("book").unwrap().move_to_room(0);
+        game_state.inventory.get_mut("flashlight").unwrap().move_to_room(0);
+
+        let item = |key: &str| Some(Object::Item(key.to_string()));
+        let door = Some(Object::Interactable("lab_entrance".to_string()));
+
+        let commands = vec![
+            ("put on the helmet", Intent::EQUIP, item("helmet"), None),
+            ("take off the helmet", Intent::UNEQUIP, item("helmet"), None),
+            ("look at the door", Intent::EXAMINE, door.clone(), None),
+            ("pick up the helmet", Intent::INVENTORY, item("helmet"), None),
+            ("push the metal door", Intent::INTERACT, door.clone(), None),
+            ("show my inventory", Intent::LIST_INVENTORY, Some(Object::Inventory), None),
+            ("put down the pendant", Intent::DROP, item("pendant"), None),
+            ("put the pendant in the book", Intent::PUT, item("pendant"), item("book")),
+            ("look in the book", Intent::SEARCH, item("book"), None),
+            ("look around", Intent::SEARCH, None, None),
+            ("unlock the book", Intent::UNLOCK, item("book"), None),
+            ("lock the book", Intent::LOCK, item("book"), None),
+            ("open the book", Intent::OPEN, item("book"), None),
+            ("shut the book", Intent::CLOSE, item("book"), None),
+            ("combine the pendant with the helmet", Intent::COMBINE, item("pendant"), item("helmet")),
+            ("walk to the south", Intent::MOVEMENT, Some(Object::Direction(Direction::S)), None),
+            ("talk to the robot", Intent::TALK, Some(Object::Npc("robot".to_string())), None),
+            ("tell the robot to climb the wall", Intent::ORDER, Some(Object::Npc("robot".to_string())), None),
+            ("turn on the flashlight", Intent::SWITCH_ON, item("flashlight"), None),
+            ("put out the flashlight", Intent::SWITCH_OFF, item("flashlight"), None),
+            ("redo", Intent::REDO, None, None),
+            ("restore", Intent::RESTORE, None, None),
+            ("save", Intent::SAVE, None, None),
+            ("undo", Intent::UNDO, None, None),
+            ("use the rusty pendant on the door", Intent::USE, item("pendant"), door.clone()),
+        ];
+
+        for (command, intent, direct_object, indirect_object) in commands {
+            let input = parse_input(command, &game_state).unwrap();
+
+            assert_eq!(input.intent, intent, "{}", command);
+            assert_eq!(input.direct_object, direct_object, "{}", command);
+            assert_eq!(input.indirect_object, indirect_object, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_addressing_an_npc() {
+        let input = parse("Robot, climb the wall!");
+
+        assert_eq!(input.intent, Intent::ORDER);
+        assert_eq!(input.direct_object, Some(Object::Npc("robot".to_string())));
+        assert_eq!(input.order, "climb wall");
+
+        let input = parse("tell the small robot to search");
+
+        assert_eq!(input.direct_object, Some(Object::Npc("robot".to_string())));
+        assert_eq!(input.order, "search");
+
+        assert_eq!(
+            parse_error("robot,"),
+            ParseError::MissingObject("tell the robot to do".to_string())
+        );
+        assert_eq!(
+            parse_error("door, open"),
+            ParseError::UnknownVerb("door".to_string())
+        );
+    }
+
+    #[test]
+    fn test_directions() {
+        let commands = vec![
+            ("north", Direction::N),
+            ("ne", Direction::NE),
+            ("u", Direction::U),
+            ("go in", Direction::IN),
+            ("walk to the southwest", Direction::SW),
+            ("climb down", Direction::D),
+        ];
+
+        for (command, direction) in commands {
+            let input = parse(command);
+
+            assert_eq!(input.intent, Intent::MOVEMENT, "{}", command);
+            assert_eq!(input.direct_object, Some(Object::Direction(direction)), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_exit_on_its_own() {
+        assert_eq!(
+            parse_error("leave"),
+            ParseError::MissingObject("leave".to_string())
+        );
+
+        let mut game_state = helmet_game();
+        game_state.rooms[0].exits.push(Exit {
+            direction: Direction::OUT,
+            target: 1,
+            ..Default::default()
+        });
+
+        for command in &["exit", "leave"] {
+            let input = parse_input(command, &game_state).unwrap();
+
+            assert_eq!(input.intent, Intent::MOVEMENT, "{}", command);
+            assert_eq!(input.direct_object, Some(Object::Direction(Direction::OUT)), "{}", command);
+        }
+
+        assert_eq!(
+            parse_input("leave the helmet", &game_state).unwrap_err(),
+            ParseError::NotApplicable {
+                verb: "leave".to_string(),
+                object: "helmet".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_named_exits() {
+        let input = parse("go through the steel door");
+
+        assert_eq!(input.intent, Intent::MOVEMENT);
+        assert_eq!(input.direct_object, Some(Object::Direction(Direction::S)));
+
+        let mut game_state = start_game();
+        game_state.current_room_idx = game_state.find_room("north_door_room").unwrap();
+
+        assert_eq!(
+            parse_input("climb down the stairs", &game_state).unwrap().direct_object,
+            Some(Object::Direction(Direction::D))
+        );
+        assert_eq!(
+            parse_input("climb up the stairs", &game_state).unwrap_err(),
+            ParseError::UnknownObject("up stairs".to_string())
+        );
+        assert_eq!(
+            parse_input("examine the stairs", &game_state).unwrap_err(),
+            ParseError::UnknownObject("stairs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_object() {
+        let mut game_state = start_game();
+        let mut door = game_state.rooms[0].interactables[0].clone();
+        door.name = "pendant".to_string();
+        game_state.rooms[0].interactables.push(door);
+
+        assert_eq!(
+            parse_input("examine pendant", &game_state).unwrap_err(),
+            ParseError::AmbiguousObject("pendant".to_string())
+        );
+    }
+
+    #[test]
+    fn test_items_in_other_rooms_are_unknown() {
+        assert_eq!(
+            parse_input("grab helmet", &start_game()).unwrap_err(),
+            ParseError::UnknownObject("helmet".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        let game_state = update(start_game(), "take".to_string());
+
+        assert_eq!(game_state.sys_message, "What do you want to take?");
+    }
+
+    // Words the parser knows about, so that generated input reaches deeper than the verb check
+    fn vocabulary() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("go".to_string()),
+            Just("use".to_string()),
+            Just("grab".to_string()),
+            Just("drop".to_string()),
+            Just("open".to_string()),
+            Just("put".to_string()),
+            Just("in".to_string()),
+            Just("from".to_string()),
+            Just("desk".to_string()),
+            Just("drawer".to_string()),
+            Just("push".to_string()),
+            Just("examine".to_string()),
+            Just("talk".to_string()),
+            Just("robot".to_string()),
+            Just("1".to_string()),
+            Just("equip".to_string()),
+            Just("show".to_string()),
+            Just("save".to_string()),
+            Just("undo".to_string()),
+            Just("south".to_string()),
+            Just("north".to_string()),
+            Just("up".to_string()),
+            Just("climb".to_string()),
+            Just("stairs".to_string()),
+            Just("door".to_string()),
+            Just("pendant".to_string()),
+            Just("helmet".to_string()),
+            Just("inventory".to_string()),
+            "\\PC{0,8}",
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_update_never_panics(input in "\\PC*") {
+            update(start_game(), input);
+        }
+
+        #[test]
+        fn test_command_sequences_never_panic(
+            commands in prop::collection::vec(prop::collection::vec(vocabulary(), 0..4), 0..12)
+        ) {
+            let mut game_state = start_game();
+            for words in commands {
+                game_state = update(game_state, words.join(" "));
+            }
+        }
+    }
+}
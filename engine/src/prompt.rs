@@ -0,0 +1,234 @@
+use serde_derive::{Deserialize, Serialize};
+
+use super::*;
+
+/// A question that the player has to answer before the game goes on. While a prompt is pending,
+/// the next input is read as an answer instead of a command.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Prompt {
+    pub question: String,
+    pub choices: Vec<Choice>,
+}
+
+/// One of the answers to a prompt and the actions it runs when it is picked.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Choice {
+    pub answer: String,
+    // Shown next to the answer when the choices are listed, such as the lines of a conversation
+    #[serde(default)]
+    pub text: String,
+    // Other ways of giving the same answer, such as "y" for "yes"
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+impl Choice {
+    fn accepts(&self, answer: &str) -> bool {
+        self.answer.to_lowercase() == answer || self.aliases.iter().any(|x| x.to_lowercase() == answer)
+    }
+}
+
+impl Prompt {
+    // "yes or no", or "red, green or blue"
+    fn answers(&self) -> String {
+        let answers: Vec<&str> = self.choices.iter().map(|x| x.answer.as_str()).collect();
+        match answers.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+            Some((last, _)) => last.to_string(),
+            None => "".to_string(),
+        }
+    }
+}
+
+// The input as it is compared to the answers of a prompt
+fn to_answer(input: &str) -> String {
+    input
+        .trim()
+        .trim_end_matches(&['.', '!', '?'][..])
+        .to_lowercase()
+}
+
+// Whether the input is a command that can be used while a prompt is pending, such as saving or
+// looking at the inventory. Answers to the prompt always count as answers
+pub fn passes_prompt(game_state: &GameState, input: &str) -> bool {
+    let prompt = match &game_state.prompt {
+        Some(prompt) => prompt,
+        None => return false,
+    };
+
+    let answer = to_answer(input);
+    if prompt.choices.iter().any(|x| x.accepts(&answer)) {
+        return false;
+    }
+
+    match parse_input(input, game_state) {
+        Ok(parsed_input) => matches!(
+            parsed_input.intent,
+            Intent::SAVE | Intent::RESTORE | Intent::LIST_INVENTORY
+        ),
+        Err(_) => false,
+    }
+}
+
+// Shows the question of a prompt along with the answers the player can give
+pub fn ask(game_state: &mut GameState, prompt: &Prompt, outcome: &mut Outcome) {
+    let question = render(&prompt.question, game_state);
+
+    if prompt.choices.iter().any(|x| !x.text.is_empty()) {
+        let lines = prompt
+            .choices
+            .iter()
+            .map(|x| format!("{}. {}", x.answer, render(&x.text, game_state)))
+            .collect();
+        outcome.item_list(&question, lines);
+    } else {
+        let answers: Vec<&str> = prompt.choices.iter().map(|x| x.answer.as_str()).collect();
+        outcome.narrate(&format!("{} ({})", question, answers.join("/")));
+    }
+    game_state.prompt = Some(prompt.clone());
+}
+
+// Reads the input as an answer to the pending prompt. Returns whether an answer was given, since
+// the question is asked again when the input is not one of its answers
+pub fn answer_prompt(game_state: &mut GameState, input: &str, outcome: &mut Outcome) -> bool {
+    let prompt = match game_state.prompt.take() {
+        Some(prompt) => prompt,
+        None => return false,
+    };
+
+    let answer = to_answer(input);
+
+    match prompt.choices.iter().find(|x| x.accepts(&answer)) {
+        Some(choice) => {
+            for action in &choice.actions {
+                run_action(game_state, action, outcome);
+            }
+            true
+        }
+        None => {
+            outcome.error(&format!("Please answer {}", prompt.answers()));
+            ask(game_state, &prompt, outcome);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_prompt_game_state() -> GameState {
+        let mut game_state = start_game();
+        game_state.triggers = vec![Trigger {
+            id: "question".to_string(),
+            event: TriggerEvent::UseItem {
+                item: "pendant".to_string(),
+            },
+            conditions: vec![],
+            actions: vec![Action::Ask {
+                prompt: Prompt {
+                    question: "Will you open the door?".to_string(),
+                    choices: vec![
+                        Choice {
+                            answer: "yes".to_string(),
+                            text: "".to_string(),
+                            aliases: vec!["y".to_string()],
+                            actions: vec![Action::SetFlag {
+                                flag: "door_open".to_string(),
+                            }],
+                        },
+                        Choice {
+                            answer: "no".to_string(),
+                            text: "".to_string(),
+                            aliases: vec!["n".to_string()],
+                            actions: vec![Action::Print {
+                                text: "You leave the door alone".to_string(),
+                            }],
+                        },
+                    ],
+                },
+            }],
+            repeat: false,
+            fired: false,
+        }];
+        game_state
+    }
+
+    #[test]
+    fn test_prompt_routes_next_input() {
+        let game_state = update(create_prompt_game_state(), "use pendant".to_string());
+
+        assert!(game_state.sys_message.ends_with("Will you open the door? (yes/no)"));
+        assert!(game_state.prompt.is_some());
+
+        let answered_state = update(game_state, "Y!".to_string());
+
+        assert!(answered_state.prompt.is_none());
+        assert!(answered_state.flags.contains("door_open"));
+    }
+
+    #[test]
+    fn test_invalid_answer_asks_again() {
+        let game_state = update(create_prompt_game_state(), "use pendant".to_string());
+        let turn = game_state.turn;
+
+        let invalid_state = update(game_state, "go north".to_string());
+
+        assert_eq!(
+            invalid_state.sys_message,
+            "Please answer yes or no\nWill you open the door? (yes/no)"
+        );
+        assert!(invalid_state.prompt.is_some());
+        assert_eq!(invalid_state.turn, turn);
+        assert_eq!(invalid_state.current_room_idx, 0);
+
+        let answered_state = update(invalid_state, "no".to_string());
+
+        assert_eq!(answered_state.sys_message, "You leave the door alone");
+        assert!(answered_state.prompt.is_none());
+    }
+
+    #[test]
+    fn test_system_commands_pass_prompt() {
+        let game_state = update(create_prompt_game_state(), "use pendant".to_string());
+        let turn = game_state.turn;
+
+        let saved_state = update(game_state, "save".to_string());
+
+        assert_eq!(saved_state.system_request, Some(SystemRequest::Save));
+        assert_eq!(
+            saved_state.sys_message,
+            "Your game has been saved.\nWill you open the door? (yes/no)"
+        );
+
+        let inventory_state = update(saved_state, "show inventory".to_string());
+
+        assert_eq!(
+            inventory_state.sys_message,
+            "You have no items in your inventory\nWill you open the door? (yes/no)"
+        );
+        assert!(inventory_state.prompt.is_some());
+        assert_eq!(inventory_state.turn, turn);
+
+        let restoring_state = update(inventory_state, "restore".to_string());
+
+        assert_eq!(restoring_state.system_request, Some(SystemRequest::Restore));
+
+        let answered_state = update(restoring_state, "yes".to_string());
+
+        assert!(answered_state.prompt.is_none());
+        assert!(answered_state.flags.contains("door_open"));
+    }
+
+    #[test]
+    fn test_prompt_survives_save() {
+        let game_state = update(create_prompt_game_state(), "use pendant".to_string());
+
+        let restored_state = GameState::restore(&game_state.save()).unwrap();
+        let answered_state = update(restored_state, "yes".to_string());
+
+        assert!(answered_state.flags.contains("door_open"));
+    }
+}
@@ -0,0 +1,162 @@
+use serde_derive::{Deserialize, Serialize};
+
+use super::*;
+
+/// A combination of two items authored in the world file. A recipe without a `result` describes a
+/// combination that does not work, with `message` explaining why.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Recipe {
+    // The names of the two items that are combined, in any order
+    pub ingredients: Vec<String>,
+    // The item the player receives. It starts out nowhere in the world
+    #[serde(default)]
+    pub result: Option<String>,
+    // Ingredients that are not used up by the combination
+    #[serde(default)]
+    pub keep: Vec<String>,
+    pub message: String,
+}
+
+impl Recipe {
+    fn matches(&self, first: &str, second: &str) -> bool {
+        self.ingredients.len() == 2
+            && self.ingredients.iter().any(|x| x == first)
+            && self.ingredients.iter().any(|x| x == second)
+    }
+}
+
+// Handles "combine <item> with <item>"
+pub fn combine_items(game_state: &mut GameState, parsed_input: &Input, outcome: &mut Outcome) {
+    let first = match &parsed_input.direct_object {
+        Some(Object::Item(key)) => key.clone(),
+        _ => return,
+    };
+
+    let second = match &parsed_input.indirect_object {
+        Some(Object::Item(key)) => key.clone(),
+        Some(_) => {
+            outcome.error(&format!(
+                "You can not combine the {} with the {}",
+                first, parsed_input.indirect_noun
+            ));
+            return;
+        }
+        None => {
+            outcome.error(&format!("What do you want to combine the {} with?", first));
+            return;
+        }
+    };
+
+    if first == second {
+        outcome.error(&format!("You can not combine the {} with itself", first));
+        return;
+    }
+
+    for key in &[&first, &second] {
+        if !game_state.inventory.get::<str>(key).is_some_and(Item::is_carried) {
+            outcome.error(&format!("You need to be carrying the {} first", key));
+            return;
+        }
+    }
+
+    let recipe = match game_state.recipes.iter().find(|x| x.matches(&first, &second)) {
+        Some(recipe) => recipe.clone(),
+        None => {
+            outcome.error(&format!("You can not combine the {} with the {}", first, second));
+            return;
+        }
+    };
+
+    let result = match &recipe.result {
+        Some(result) => result,
+        None => {
+            outcome.error(&recipe.message);
+            return;
+        }
+    };
+
+    for key in recipe.ingredients.iter().filter(|x| !recipe.keep.contains(x)) {
+        if let Some(item) = game_state.inventory.get_mut::<str>(key) {
+            item.move_to_nowhere();
+        }
+    }
+
+    if let Some(item) = game_state.inventory.get_mut::<str>(result) {
+        item.move_to_inventory();
+        outcome.narrate(&recipe.message);
+        outcome.event(GameEvent::ItemCrafted(result.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    fn create_recipe_game_state() -> GameState {
+        GameState {
+            recipes: vec![
+                Recipe {
+                    ingredients: vec!["helmet".to_string(), "scanner".to_string()],
+                    result: Some("scanning helmet".to_string()),
+                    keep: vec![],
+                    message: "The scanner clicks onto the side of the helmet".to_string(),
+                },
+                Recipe {
+                    ingredients: vec!["helmet".to_string(), "pendant".to_string()],
+                    result: None,
+                    keep: vec![],
+                    message: "The pendant is too large to fit in the helmet".to_string(),
+                },
+            ],
+            ..game_with_items(vec![
+                item("helmet", ItemState::Inventory),
+                item("scanner", ItemState::Inventory),
+                item("pendant", ItemState::Inventory),
+                item("glue", ItemState::Inventory),
+                item("scanning helmet", ItemState::Nowhere),
+            ])
+        }
+    }
+
+    #[test]
+    fn test_combine_items() {
+        let game_state = create_recipe_game_state();
+
+        let after_state = update(game_state, "combine scanner with the helmet".to_string());
+
+        assert_eq!(after_state.sys_message, "The scanner clicks onto the side of the helmet");
+        assert!(after_state.inventory.get("scanning helmet").unwrap().is_in_inventory());
+        assert_eq!(after_state.inventory.get("helmet").unwrap().get_location(), &ItemState::Nowhere);
+        assert_eq!(after_state.inventory.get("scanner").unwrap().get_location(), &ItemState::Nowhere);
+        assert_eq!(
+            after_state.outcome.events,
+            vec![GameEvent::ItemCrafted("scanning helmet".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_combine_keeps_ingredients() {
+        let mut game_state = create_recipe_game_state();
+        game_state.recipes[0].keep = vec!["helmet".to_string()];
+
+        let after_state = update(game_state, "combine helmet with scanner".to_string());
+
+        assert!(after_state.inventory.get("helmet").unwrap().is_in_inventory());
+        assert_eq!(after_state.inventory.get("scanner").unwrap().get_location(), &ItemState::Nowhere);
+    }
+
+    #[test]
+    fn test_failed_combination() {
+        let game_state = create_recipe_game_state();
+
+        let authored_state = update(game_state.clone(), "combine pendant with helmet".to_string());
+
+        assert_eq!(authored_state.sys_message, "The pendant is too large to fit in the helmet");
+        assert!(authored_state.inventory.get("pendant").unwrap().is_in_inventory());
+
+        let default_state = update(game_state, "combine glue with pendant".to_string());
+
+        assert_eq!(default_state.sys_message, "You can not combine the glue with the pendant");
+    }
+}
@@ -0,0 +1,352 @@
+use std::fmt;
+
+use serde_derive::Serialize;
+use serde_json::Value;
+
+use super::{GameState, ItemState, Outcome};
+
+pub const SAVE_FORMAT_VERSION: u32 = 3;
+
+/// Actions that the engine can not perform itself and asks the frontend to carry out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SystemRequest {
+    Save,
+    Restore,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SaveError {
+    MissingVersion,
+    Migration { from: u32, reason: String },
+    NewerVersion(u32),
+    Parse(String),
+    UnknownVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::MissingVersion => write!(f, "The saved game has no format version"),
+            SaveError::Migration { from, reason } => write!(
+                f,
+                "The saved game could not be upgraded from version {}: {}",
+                from, reason
+            ),
+            SaveError::NewerVersion(version) => write!(
+                f,
+                "The saved game was made by a newer version of the game (format {}, expected at most {})",
+                version, SAVE_FORMAT_VERSION
+            ),
+            SaveError::Parse(msg) => write!(f, "The saved game could not be read: {}", msg),
+            SaveError::UnknownVersion(version) => {
+                write!(f, "Save format version {} does not exist", version)
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SaveGameRef<'a> {
+    version: u32,
+    state: &'a GameState,
+}
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+// MIGRATIONS[n] upgrades the `state` of a version n + 1 save to version n + 2.  Whenever the
+// shape of GameState changes, bump SAVE_FORMAT_VERSION and append a migration here.
+static MIGRATIONS: &[Migration] = &[migrate_room_items, migrate_room_visits];
+
+fn rooms_mut(state: &mut Value) -> Result<&mut Vec<Value>, String> {
+    state
+        .get_mut("rooms")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| "the save has no rooms".to_string())
+}
+
+// Version 2 records the room an item lies in on the item itself instead of listing the item in
+// the room
+fn migrate_room_items(mut state: Value) -> Result<Value, String> {
+    let mut item_rooms = Vec::new();
+    let rooms = rooms_mut(&mut state)?;
+    for (idx, room) in rooms.iter_mut().enumerate() {
+        let room = room.as_object_mut().ok_or("a room is not an object")?;
+        if let Some(Value::Array(items)) = room.remove("items") {
+            for item in items {
+                item_rooms.push((item, idx));
+            }
+        }
+    }
+
+    let inventory = state
+        .get_mut("inventory")
+        .and_then(Value::as_object_mut)
+        .ok_or("the save has no inventory")?;
+    for (key, item) in inventory.iter_mut() {
+        if item.get("location") == Some(&Value::from("Room")) {
+            item["location"] = match item_rooms.iter().find(|(name, _)| name == key) {
+                Some((_, idx)) => serde_json::json!({ "Room": idx }),
+                None => Value::from("Nowhere"),
+            };
+        }
+    }
+
+    Ok(state)
+}
+
+// Version 3 counts how many times the player has been in each room. Older saves only tell that the
+// player has been in the room they are standing in
+fn migrate_room_visits(mut state: Value) -> Result<Value, String> {
+    let room_idx = state
+        .get("current_room_idx")
+        .and_then(Value::as_u64)
+        .ok_or("the save has no current room")? as usize;
+    let room = rooms_mut(&mut state)?
+        .get_mut(room_idx)
+        .and_then(Value::as_object_mut)
+        .ok_or("the current room does not exist")?;
+    room.entry("visits").or_insert_with(|| Value::from(1));
+
+    Ok(state)
+}
+
+fn migrate(mut state: Value, from_version: u32) -> Result<Value, SaveError> {
+    for version in from_version..SAVE_FORMAT_VERSION {
+        let migration = MIGRATIONS[(version - 1) as usize];
+        state = migration(state).map_err(|reason| SaveError::Migration {
+            from: version,
+            reason,
+        })?;
+    }
+
+    Ok(state)
+}
+
+/// Serializes the full game state into a versioned save string.
+pub fn save_game(state: &GameState) -> String {
+    serde_json::to_string(&SaveGameRef {
+        version: SAVE_FORMAT_VERSION,
+        state,
+    })
+    .expect("the game state can always be serialized")
+}
+
+/// Rebuilds a game state from a string produced by `save_game`, upgrading saves made by older
+/// versions of the game.
+pub fn restore_game(save: &str) -> Result<GameState, SaveError> {
+    let mut save_game: Value =
+        serde_json::from_str(save).map_err(|e| SaveError::Parse(e.to_string()))?;
+
+    let version = match save_game.get("version").and_then(Value::as_u64) {
+        Some(version) => version as u32,
+        None => return Err(SaveError::MissingVersion),
+    };
+
+    if version == 0 {
+        return Err(SaveError::UnknownVersion(version));
+    } else if version > SAVE_FORMAT_VERSION {
+        return Err(SaveError::NewerVersion(version));
+    }
+
+    let state = match save_game.get_mut("state") {
+        Some(state) => migrate(state.take(), version)?,
+        None => return Err(SaveError::Parse("the save has no game state".to_string())),
+    };
+
+    let mut state: GameState =
+        serde_json::from_value(state).map_err(|e| SaveError::Parse(e.to_string()))?;
+    check_state(&state)?;
+
+    let mut outcome = Outcome::default();
+    outcome.room_description(&state.describe_room(state.current_room_idx));
+    state.sys_message = outcome.message();
+    state.outcome = outcome;
+
+    Ok(state)
+}
+
+// A save can be edited by hand, so every room index, item name and era in it is checked before
+// the game state is used
+fn check_state(state: &GameState) -> Result<(), SaveError> {
+    let check_room = |room_idx: usize| {
+        if room_idx < state.rooms.len() {
+            Ok(())
+        } else {
+            Err(SaveError::Parse(format!("room {} does not exist", room_idx)))
+        }
+    };
+
+    check_room(state.current_room_idx)?;
+    for room in &state.rooms {
+        for exit in &room.exits {
+            check_room(exit.target)?;
+        }
+    }
+    for npc in &state.npcs {
+        check_room(npc.room_idx)?;
+    }
+    if !state.era.is_empty() && !state.rooms.iter().any(|x| x.era == state.era) {
+        return Err(SaveError::Parse(format!("era {} does not exist", state.era)));
+    }
+    for item in state.inventory.values() {
+        match item.get_location() {
+            ItemState::Room(room_idx) => check_room(*room_idx)?,
+            ItemState::Container(key) if !state.inventory.contains_key(key) => {
+                return Err(SaveError::Parse(format!("item {} does not exist", key)));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_save_and_restore() {
+        let mut game_state = start_game();
+        game_state.rooms[0].interactables[0].interact();
+        game_state.rooms[0].exits[0].unlock();
+        game_state.inventory.get_mut("helmet").unwrap().move_to_inventory();
+        game_state.current_room_idx = 2;
+
+        let restored_state = restore_game(&save_game(&game_state)).unwrap();
+
+        assert_eq!(restored_state.current_room_idx, 2);
+        assert!(restored_state.rooms[0].interactables[0].is_interacted());
+        assert!(!restored_state.rooms[0].exits[0].is_locked());
+        assert!(restored_state.inventory.get("helmet").unwrap().is_in_inventory());
+        assert_eq!(
+            restored_state.sys_message,
+            "You find yourself in a room. There is a door to the north, a door to the east and a dark stairway leading down. A heavy desk stands against the wall and a stopped clock hangs above it."
+        );
+    }
+
+    // A save made with every historical version of the save format, paired with its version
+    const HISTORICAL_SAVES: &[(u32, &str)] = &[
+        (1, include_str!("../fixtures/saves/v1.json")),
+        (2, include_str!("../fixtures/saves/v2.json")),
+        (3, include_str!("../fixtures/saves/v3.json")),
+    ];
+
+    #[test]
+    fn test_migrations_cover_every_version() {
+        assert_eq!(MIGRATIONS.len() as u32, SAVE_FORMAT_VERSION - 1);
+        assert_eq!(HISTORICAL_SAVES.len() as u32, SAVE_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_restore_historical_saves() {
+        for (version, save) in HISTORICAL_SAVES {
+            let restored_state = restore_game(save)
+                .unwrap_or_else(|e| panic!("version {} save failed to restore: {}", version, e));
+
+            // Every historical save was made after unlocking the entrance and grabbing the helmet
+            assert_eq!(restored_state.current_room_idx, 2);
+            assert!(!restored_state.rooms[0].exits[0].is_locked());
+            assert!(restored_state.rooms[0].interactables[0].is_interacted());
+            assert!(restored_state.inventory.get("helmet").unwrap().is_in_inventory());
+            assert!(restored_state.rooms[2].visits > 0);
+
+            let resaved_state = restore_game(&save_game(&restored_state)).unwrap();
+
+            assert_eq!(
+                serde_json::to_value(&resaved_state).unwrap(),
+                serde_json::to_value(&restored_state).unwrap(),
+                "version {} save did not survive a round trip",
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn test_restore_newer_version() {
+        let save = r#"{"version": 99, "state": {}}"#;
+
+        assert_eq!(restore_game(save).unwrap_err(), SaveError::NewerVersion(99));
+    }
+
+    #[test]
+    fn test_restore_unknown_version() {
+        let save = r#"{"version": 0, "state": {}}"#;
+
+        assert_eq!(restore_game(save).unwrap_err(), SaveError::UnknownVersion(0));
+    }
+
+    #[test]
+    fn test_restore_missing_version() {
+        let save = r#"{"state": {}}"#;
+
+        assert_eq!(restore_game(save).unwrap_err(), SaveError::MissingVersion);
+    }
+
+    #[test]
+    fn test_restore_invalid_save() {
+        match restore_game("not a save") {
+            Err(SaveError::Parse(_)) => {}
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_restore_out_of_range_references() {
+        let mut game_state = start_game();
+        game_state.rooms[0].exits[0].target = 99;
+
+        assert_eq!(
+            restore_game(&save_game(&game_state)).unwrap_err(),
+            SaveError::Parse("room 99 does not exist".to_string())
+        );
+
+        let mut game_state = start_game();
+        game_state.inventory.get_mut("helmet").unwrap().move_to_room(42);
+
+        assert_eq!(
+            restore_game(&save_game(&game_state)).unwrap_err(),
+            SaveError::Parse("room 42 does not exist".to_string())
+        );
+
+        let mut game_state = start_game();
+        game_state
+            .inventory
+            .get_mut("helmet")
+            .unwrap()
+            .move_to_container("strongbox");
+
+        assert_eq!(
+            restore_game(&save_game(&game_state)).unwrap_err(),
+            SaveError::Parse("item strongbox does not exist".to_string())
+        );
+
+        let mut game_state = start_game();
+        game_state.npcs[0].room_idx = 42;
+
+        assert_eq!(
+            restore_game(&save_game(&game_state)).unwrap_err(),
+            SaveError::Parse("room 42 does not exist".to_string())
+        );
+
+        let mut game_state = start_game();
+        game_state.era = "future".to_string();
+
+        assert_eq!(
+            restore_game(&save_game(&game_state)).unwrap_err(),
+            SaveError::Parse("era future does not exist".to_string())
+        );
+    }
+
+    #[test]
+    fn test_save_command_requests_save() {
+        let game_state = update(start_game(), "save".to_string());
+
+        assert_eq!(game_state.system_request, Some(SystemRequest::Save));
+
+        let next_state = update(game_state, "show inventory".to_string());
+
+        assert_eq!(next_state.system_request, None);
+    }
+}
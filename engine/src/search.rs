@@ -0,0 +1,222 @@
+use serde_derive::{Deserialize, Serialize};
+
+use super::*;
+
+/// Whether something in a room has to be searched for before the player can see it. Items,
+/// interactables and exits all carry one.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Concealment {
+    // Set for things that start out hidden
+    #[serde(default)]
+    pub hidden: bool,
+    // Whether a search has turned it up
+    #[serde(default)]
+    pub found: bool,
+    // An item that has to be carried or worn for a search to find it
+    #[serde(default)]
+    pub revealed_by: String,
+}
+
+impl Concealment {
+    pub fn is_visible(&self) -> bool {
+        !self.hidden || self.found
+    }
+
+    // Whether a search of the room would turn this up right now
+    fn can_be_found(&self, inventory: &HashMap<String, Item>) -> bool {
+        !self.is_visible()
+            && (self.revealed_by.is_empty()
+                || inventory.get(&self.revealed_by).is_some_and(Item::is_carried))
+    }
+}
+
+// Handles "search" and "look around" without a container to search in
+pub fn search_room(game_state: &mut GameState, outcome: &mut Outcome) {
+    let room_idx = game_state.current_room_idx;
+    if !game_state.is_lit(room_idx) {
+        outcome.error("It is too dark to search here");
+        return;
+    }
+
+    let inventory = &game_state.inventory;
+    let room = &mut game_state.rooms[room_idx];
+
+    let mut items: Vec<String> = inventory
+        .values()
+        .filter(|x| x.is_in_room(room_idx) && x.concealment.can_be_found(inventory))
+        .map(|x| x.get_name().to_string())
+        .collect();
+    items.sort();
+    let mut found = items.clone();
+
+    for x in room
+        .interactables
+        .iter_mut()
+        .filter(|x| x.concealment.can_be_found(inventory))
+    {
+        x.concealment.found = true;
+        found.push(x.name.clone());
+    }
+
+    for exit in room.exits.iter_mut().filter(|x| x.concealment.can_be_found(inventory)) {
+        exit.concealment.found = true;
+        found.push(format!("a way {}", exit.direction.get_name()));
+    }
+
+    for key in &items {
+        if let Some(item) = game_state.inventory.get_mut(key) {
+            item.concealment.found = true;
+        }
+    }
+
+    if found.is_empty() {
+        outcome.narrate("You search the area but find nothing new");
+    } else {
+        outcome.item_list("Your search turns up:", found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    // A room with a hidden coin, a hidden lever that takes the helmet to find and a hidden exit
+    // to the east
+    fn create_search_game_state() -> GameState {
+        let hidden = Concealment {
+            hidden: true,
+            ..Default::default()
+        };
+
+        GameState {
+            rooms: vec![
+                Room {
+                    description: "Test Room 1".to_string(),
+                    interactables: vec![Interactable {
+                        id: "lever".to_string(),
+                        name: "lever".to_string(),
+                        before_interaction_description: "A rusty lever".to_string(),
+                        concealment: Concealment {
+                            hidden: true,
+                            revealed_by: "helmet".to_string(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }],
+                    exits: vec![Exit {
+                        direction: Direction::E,
+                        target: 1,
+                        concealment: hidden.clone(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+                Room {
+                    description: "Test Room 2".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..game_with_items(vec![
+                Item {
+                    concealment: hidden,
+                    ..item("coin", ItemState::Room(0))
+                },
+                item("buster", ItemState::Room(0)),
+                item("helmet", ItemState::Nowhere),
+            ])
+        }
+    }
+
+    #[test]
+    fn test_hidden_things_are_unknown() {
+        let game_state = create_search_game_state();
+
+        assert_eq!(game_state.describe_room(0), "Test Room 1\nOn the ground you see: buster");
+        assert_eq!(
+            update(game_state.clone(), "take coin".to_string()).sys_message,
+            "You don't see any coin here."
+        );
+        assert_eq!(
+            update(game_state, "go east".to_string()).sys_message,
+            "There is no exit leaving east"
+        );
+    }
+
+    #[test]
+    fn test_use_on_hidden_exit() {
+        let mut game_state = create_search_game_state();
+        game_state.rooms[0].exits[0].interactable_id = "lever".to_string();
+        game_state.rooms[0].interactables[0].prerequisite_item = "buster".to_string();
+        game_state.inventory.get_mut("buster").unwrap().move_to_inventory();
+
+        assert_eq!(
+            update(game_state, "use buster on east".to_string()).sys_message,
+            "There is no exit leaving east"
+        );
+    }
+
+    #[test]
+    fn test_search_reveals_hidden_things() {
+        let game_state = create_search_game_state();
+
+        let searched_state = update(game_state, "look around".to_string());
+
+        assert_eq!(searched_state.sys_message, "Your search turns up:\ncoin\na way east");
+        assert_eq!(
+            searched_state.describe_room(0),
+            "Test Room 1\nOn the ground you see: buster, coin\nYou have found: a way east"
+        );
+
+        let moved_state = update(searched_state, "go east".to_string());
+
+        assert_eq!(moved_state.current_room_idx, 1);
+    }
+
+    #[test]
+    fn test_search_with_required_item() {
+        let mut game_state = update(create_search_game_state(), "search".to_string());
+
+        assert_eq!(
+            update(game_state.clone(), "search".to_string()).sys_message,
+            "You search the area but find nothing new"
+        );
+
+        let helmet = game_state.inventory.get_mut("helmet").unwrap();
+        helmet.move_to_inventory();
+        helmet.slot = Some(Slot::Head);
+        let equipped_state = update(game_state, "wear helmet".to_string());
+        let searched_state = update(equipped_state, "search".to_string());
+
+        assert_eq!(searched_state.sys_message, "Your search turns up:\nlever");
+
+        let restored_state = GameState::restore(&searched_state.save()).unwrap();
+
+        assert_eq!(
+            update(restored_state, "examine lever".to_string()).sys_message,
+            "A rusty lever"
+        );
+    }
+
+    #[test]
+    fn test_crevice_in_the_helmet_room() {
+        let mut game_state = start_game();
+        for input in &["use pendant on door", "go south", "go east"] {
+            game_state = update(game_state, input.to_string());
+        }
+
+        assert_eq!(game_state.current_room_idx, game_state.find_room("helmet_room").unwrap());
+
+        let searched_state = update(game_state, "search the crevice".to_string());
+
+        assert_eq!(searched_state.sys_message, "You search the area but find nothing new");
+
+        let helmet_state = update(searched_state, "take helmet".to_string());
+        let searched_state = update(helmet_state, "search the crevice".to_string());
+
+        assert_eq!(searched_state.sys_message, "Your search turns up:\nchip");
+        assert!(searched_state
+            .describe_room(searched_state.current_room_idx)
+            .ends_with("On the ground you see: chip"));
+    }
+}
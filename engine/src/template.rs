@@ -0,0 +1,240 @@
+use super::*;
+
+// A piece of a parsed description
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    If {
+        condition: Condition,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+// How a list of nodes came to an end while parsing
+enum Terminator {
+    Else,
+    End,
+    Eof,
+}
+
+fn parse_number<T: std::str::FromStr>(word: &str, text: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("`{}` is not a number in `{}`", word, text))
+}
+
+// Parses the condition of an `{if ...}` tag, such as "flag seen_eda" or "not has helmet"
+fn parse_condition(text: &str) -> Result<Condition, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["not", ..] => Ok(Condition::Not {
+            condition: Box::new(parse_condition(&words[1..].join(" "))?),
+        }),
+        ["flag", flag] => Ok(Condition::Flag {
+            flag: flag.to_string(),
+        }),
+        ["has", name @ ..] if !name.is_empty() => Ok(Condition::HasItem {
+            item: name.join(" "),
+        }),
+        ["visited", room] => Ok(Condition::Visited {
+            room: room.to_string(),
+        }),
+        ["in", room] => Ok(Condition::InRoom {
+            room: room.to_string(),
+        }),
+        ["visits", room, times] => Ok(Condition::Visits {
+            room: room.to_string(),
+            at_least: parse_number(times, text)?,
+        }),
+        ["var", name, value] => Ok(Condition::Variable {
+            name: name.to_string(),
+            at_least: parse_number(value, text)?,
+        }),
+        ["seen", memory] => Ok(Condition::SeenMemory {
+            memory: memory.to_string(),
+        }),
+        ["era", era] => Ok(Condition::InEra { era: era.to_string() }),
+        _ => Err(format!("`{}` is not a condition", text)),
+    }
+}
+
+// Parses nodes until an `{else}`, an `{end}` or the end of the text
+fn parse_nodes(text: &mut &str) -> Result<(Vec<Node>, Terminator), String> {
+    let mut nodes = vec![];
+
+    loop {
+        let start = match text.find('{') {
+            Some(start) => start,
+            None => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text.to_string()));
+                }
+                *text = "";
+                return Ok((nodes, Terminator::Eof));
+            }
+        };
+
+        if start > 0 {
+            nodes.push(Node::Text(text[..start].to_string()));
+        }
+
+        let end = match text[start..].find('}') {
+            Some(end) => start + end,
+            None => return Err(format!("`{}` has no closing brace", &text[start..])),
+        };
+        let tag = text[start + 1..end].trim().to_string();
+        *text = &text[end + 1..];
+
+        if tag == "else" {
+            return Ok((nodes, Terminator::Else));
+        } else if tag == "end" {
+            return Ok((nodes, Terminator::End));
+        } else if let Some(condition) = tag.strip_prefix("if ") {
+            let condition = parse_condition(condition)?;
+
+            let (then, terminator) = parse_nodes(text)?;
+            let otherwise = match terminator {
+                Terminator::Else => match parse_nodes(text)? {
+                    (otherwise, Terminator::End) => otherwise,
+                    _ => return Err(format!("`{{{}}}` is missing its {{end}}", tag)),
+                },
+                Terminator::End => vec![],
+                Terminator::Eof => return Err(format!("`{{{}}}` is missing its {{end}}", tag)),
+            };
+
+            nodes.push(Node::If {
+                condition,
+                then,
+                otherwise,
+            });
+        } else {
+            return Err(format!("`{{{}}}` is not a known tag", tag));
+        }
+    }
+}
+
+fn parse(mut text: &str) -> Result<Vec<Node>, String> {
+    match parse_nodes(&mut text)? {
+        (nodes, Terminator::Eof) => Ok(nodes),
+        (_, Terminator::Else) => Err("`{else}` is outside of an `{if}`".to_string()),
+        (_, Terminator::End) => Err("`{end}` is outside of an `{if}`".to_string()),
+    }
+}
+
+fn render_nodes(nodes: &[Node], game_state: &GameState, rendered: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => rendered.push_str(text),
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if condition.holds(game_state) {
+                    render_nodes(then, game_state, rendered);
+                } else {
+                    render_nodes(otherwise, game_state, rendered);
+                }
+            }
+        }
+    }
+}
+
+/// Checks that a description only uses the template syntax correctly.
+pub fn check_template(text: &str) -> Result<(), String> {
+    parse(text).map(|_| ())
+}
+
+/// Renders a description written in the template syntax. Text between `{if <condition>}` and
+/// `{end}` is only shown while the condition holds, and an optional `{else}` gives the text shown
+/// otherwise. The conditions are `flag <flag>`, `has <item>`, `visited <room>`, `in <room>`,
+/// `visits <room> <times>`, `var <variable> <value>`, `seen <memory>` and `era <era>`, and any of
+/// them can be negated with `not`.
+pub fn render(text: &str, game_state: &GameState) -> String {
+    match parse(text) {
+        Ok(nodes) => {
+            let mut rendered = String::new();
+            render_nodes(&nodes, game_state, &mut rendered);
+            rendered
+        }
+        // World files are checked when they are loaded, so this only happens with hand edited saves
+        Err(_) => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        let game_state = start_game();
+
+        assert_eq!(render("Nothing to see here", &game_state), "Nothing to see here");
+    }
+
+    #[test]
+    fn test_flag_condition() {
+        let mut game_state = start_game();
+        let text = "The room is quiet.{if flag seen_eda} You remember Eda laughing here.{else} It feels empty.{end}";
+
+        assert_eq!(render(text, &game_state), "The room is quiet. It feels empty.");
+
+        game_state.flags.insert("seen_eda".to_string());
+
+        assert_eq!(render(text, &game_state), "The room is quiet. You remember Eda laughing here.");
+    }
+
+    #[test]
+    fn test_nested_conditions() {
+        let mut game_state = start_game();
+        let text = "{if has pendant}Your pendant{if not visits ruin_entrance 2} hums{end}.{end}";
+
+        assert_eq!(render(text, &game_state), "Your pendant hums.");
+
+        game_state.rooms[0].visits = 2;
+
+        assert_eq!(render(text, &game_state), "Your pendant.");
+
+        game_state.inventory.get_mut("pendant").unwrap().move_to_nowhere();
+
+        assert_eq!(render(text, &game_state), "");
+    }
+
+    #[test]
+    fn test_variable_condition() {
+        let mut game_state = start_game();
+        let text = "{if var batteries 2}The lights are on{else}It is dark{end}";
+
+        game_state.variables.insert("batteries".to_string(), 1);
+
+        assert_eq!(render(text, &game_state), "It is dark");
+
+        game_state.variables.insert("batteries".to_string(), 2);
+
+        assert_eq!(render(text, &game_state), "The lights are on");
+    }
+
+    #[test]
+    fn test_seen_memory_condition() {
+        let mut game_state = start_game();
+        let text = "{if seen albert_and_robot}You know who built the robot{end}";
+
+        assert_eq!(render(text, &game_state), "");
+
+        game_state.memories_seen.insert("albert_and_robot".to_string());
+
+        assert_eq!(render(text, &game_state), "You know who built the robot");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(check_template("{if flag a}missing end").is_err());
+        assert!(check_template("stray {end}").is_err());
+        assert!(check_template("{if smell roses}x{end}").is_err());
+        assert!(check_template("{if visits room many}x{end}").is_err());
+        assert!(check_template("{unknown}").is_err());
+        assert!(check_template("{if flag a}a{else}b{end}").is_ok());
+    }
+}
@@ -0,0 +1,53 @@
+// Builders for the game states that the tests of several modules share
+
+use super::*;
+
+/// An item with a plain description, lying at `location`.
+pub fn item(name: &str, location: ItemState) -> Item {
+    Item {
+        name: name.to_string(),
+        description: format!("A {}", name),
+        location,
+        ..Default::default()
+    }
+}
+
+/// A character with a plain description, standing in the room at `room_idx`.
+pub fn npc(id: &str, room_idx: usize) -> Npc {
+    Npc {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: format!("A {}", id),
+        room_idx,
+        ..Default::default()
+    }
+}
+
+/// A game with a single empty room, holding the given items.
+pub fn game_with_items(items: Vec<Item>) -> GameState {
+    GameState {
+        current_room_idx: 0,
+        inventory: items.into_iter().map(|x| (x.name.clone(), x)).collect(),
+        sys_message: "".to_string(),
+        rooms: vec![Room {
+            description: "Test Room 1".to_string(),
+            ..Default::default()
+        }],
+        system_request: None,
+        outcome: Outcome::default(),
+        ..Default::default()
+    }
+}
+
+/// The default world with the player wearing the helmet.
+pub fn game_wearing_helmet() -> GameState {
+    let mut game_state = start_game();
+    game_state.inventory.get_mut("helmet").unwrap().equip();
+    game_state
+}
+
+/// Puts the player of `game_state` in the room with the given id, without taking a turn.
+pub fn in_room(mut game_state: GameState, room: &str) -> GameState {
+    game_state.current_room_idx = game_state.find_room(room).unwrap();
+    game_state
+}
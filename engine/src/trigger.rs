@@ -0,0 +1,670 @@
+use serde_derive::{Deserialize, Serialize};
+
+use super::*;
+
+// Triggers can set flags that fire other triggers, so the number fired in a single turn is capped
+// to keep badly authored worlds from looping forever
+const MAX_TRIGGERS_PER_TURN: usize = 64;
+
+/// What has to happen in the world for a trigger to be considered.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerEvent {
+    EnterRoom { room: String },
+    TakeItem { item: String },
+    Interact { interactable: String },
+    UseItem { item: String },
+    // The given number of turns has passed since the start of the game
+    Turn { count: u32 },
+    FlagSet { flag: String },
+    TalkTo { npc: String },
+    // The player has returned to the present from a memory
+    LeaveMemory { memory: String },
+    ChangeEra { era: String },
+    // The player tried to find their way through a dark room without a light
+    Stumble { room: String },
+}
+
+/// Something about the world that has to be true for a trigger to fire.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    Flag { flag: String },
+    HasItem { item: String },
+    Visited { room: String },
+    // The player is currently in the room
+    InRoom { room: String },
+    // The player has been in the room at least this many times
+    Visits { room: String, at_least: u32 },
+    Variable { name: String, at_least: i64 },
+    ItemInRoom { item: String, room: String },
+    ItemInContainer { item: String, container: String },
+    SeenMemory { memory: String },
+    InEra { era: String },
+    Not { condition: Box<Condition> },
+}
+
+/// Where a MoveItem action puts an item.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemDestination {
+    Inventory,
+    Nowhere,
+    // The id of a room
+    Room(String),
+    // The name of a container item
+    Container(String),
+}
+
+/// A change to the world made by a trigger.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Print { text: String },
+    SetFlag { flag: String },
+    ClearFlag { flag: String },
+    SetVariable { name: String, value: i64 },
+    AddToVariable { name: String, amount: i64 },
+    UnlockExit { room: String, direction: String },
+    LockExit { room: String, direction: String },
+    MoveItem { item: String, to: ItemDestination },
+    SpawnInteractable { room: String, interactable: Box<Interactable> },
+    SetRoomDescription { room: String, description: String },
+    LightRoom { room: String },
+    DarkenRoom { room: String },
+    Win { ending: String },
+    Lose { reason: String },
+    Ask { prompt: Prompt },
+    // Starts a conversation with a character at one of its dialogue nodes
+    Talk { npc: String, node: String },
+    // Takes the player into a memory until they return to the present
+    Remember { memory: String },
+    // Moves the player to the version of their location in another era
+    ChangeEra { era: String },
+}
+
+/// Runs its actions when its event happens and all of its conditions hold. Triggers fire once
+/// unless they are marked `repeat`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Trigger {
+    pub id: String,
+    pub event: TriggerEvent,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    pub actions: Vec<Action>,
+    #[serde(default)]
+    pub repeat: bool,
+    #[serde(default)]
+    pub fired: bool,
+}
+
+impl TriggerEvent {
+    fn matches(&self, game_state: &GameState, event: &GameEvent) -> bool {
+        match (self, event) {
+            (TriggerEvent::EnterRoom { room }, GameEvent::RoomEntered(room_idx)) => {
+                game_state.rooms.get(*room_idx).is_some_and(|x| &x.id == room)
+            }
+            (TriggerEvent::TakeItem { item }, GameEvent::ItemTaken(name)) => item == name,
+            (TriggerEvent::Interact { interactable }, GameEvent::InteractableTriggered(id)) => {
+                interactable == id
+            }
+            (TriggerEvent::UseItem { item }, GameEvent::ItemUsed(name)) => item == name,
+            (TriggerEvent::FlagSet { flag }, GameEvent::FlagSet(name)) => flag == name,
+            (TriggerEvent::TalkTo { npc }, GameEvent::TalkedTo(id)) => npc == id,
+            (TriggerEvent::LeaveMemory { memory }, GameEvent::MemoryLeft(id)) => memory == id,
+            (TriggerEvent::ChangeEra { era }, GameEvent::EraChanged(id)) => era == id,
+            (TriggerEvent::Stumble { room }, GameEvent::Stumbled(room_idx)) => {
+                game_state.rooms.get(*room_idx).is_some_and(|x| &x.id == room)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Condition {
+    pub fn holds(&self, game_state: &GameState) -> bool {
+        let item = |name: &str| game_state.inventory.get(name);
+
+        match self {
+            Condition::Flag { flag } => game_state.flags.contains(flag),
+            Condition::HasItem { item: name } => item(name).is_some_and(Item::is_carried),
+            Condition::Visited { room } => game_state
+                .find_room(room)
+                .is_some_and(|room_idx| game_state.rooms[room_idx].visits > 0),
+            Condition::InRoom { room } => game_state.find_room(room) == Some(game_state.current_room_idx),
+            Condition::Visits { room, at_least } => game_state
+                .find_room(room)
+                .is_some_and(|room_idx| game_state.rooms[room_idx].visits >= *at_least),
+            Condition::Variable { name, at_least } => {
+                game_state.variables.get(name).cloned().unwrap_or(0) >= *at_least
+            }
+            Condition::ItemInRoom { item: name, room } => match game_state.find_room(room) {
+                Some(room_idx) => item(name).is_some_and(|x| x.is_in_room(room_idx)),
+                None => false,
+            },
+            Condition::ItemInContainer {
+                item: name,
+                container,
+            } => item(name).is_some_and(|x| x.is_in_container(container)),
+            Condition::SeenMemory { memory } => game_state.memories_seen.contains(memory),
+            Condition::InEra { era } => &game_state.era == era,
+            Condition::Not { condition } => !condition.holds(game_state),
+        }
+    }
+
+    fn collect_references<'a>(&'a self, references: &mut References<'a>) {
+        match self {
+            Condition::Flag { .. } | Condition::Variable { .. } => {}
+            Condition::HasItem { item } => references.items.push(item),
+            Condition::Visited { room } | Condition::InRoom { room } | Condition::Visits { room, .. } => {
+                references.rooms.push(room)
+            }
+            Condition::ItemInRoom { item, room } => {
+                references.items.push(item);
+                references.rooms.push(room);
+            }
+            Condition::ItemInContainer { item, container } => {
+                references.items.push(item);
+                references.items.push(container);
+            }
+            Condition::SeenMemory { memory } => references.memories.push(memory),
+            Condition::InEra { era } => references.eras.push(era),
+            Condition::Not { condition } => condition.collect_references(references),
+        }
+    }
+}
+
+impl Trigger {
+    fn can_fire(&self) -> bool {
+        self.repeat || !self.fired
+    }
+
+    /// Everything the trigger refers to, so that world files can be checked when they are loaded.
+    pub fn references(&self) -> References<'_> {
+        let mut references = References::collect(&self.conditions, &self.actions);
+
+        match &self.event {
+            TriggerEvent::EnterRoom { room } | TriggerEvent::Stumble { room } => references.rooms.push(room),
+            TriggerEvent::TakeItem { item } | TriggerEvent::UseItem { item } => {
+                references.items.push(item)
+            }
+            TriggerEvent::TalkTo { npc } => references.npcs.push(npc),
+            TriggerEvent::LeaveMemory { memory } => references.memories.push(memory),
+            TriggerEvent::ChangeEra { era } => references.eras.push(era),
+            _ => {}
+        }
+
+        references
+    }
+}
+
+/// The things in the world that a list of conditions and actions refers to, along with the texts
+/// written in the template syntax.
+#[derive(Debug, Default)]
+pub struct References<'a> {
+    // Room ids
+    pub rooms: Vec<&'a str>,
+    // Item names
+    pub items: Vec<&'a str>,
+    // Npc ids
+    pub npcs: Vec<&'a str>,
+    // Npc ids along with the id of one of their dialogue nodes
+    pub dialogue: Vec<(&'a str, &'a str)>,
+    pub endings: Vec<&'a str>,
+    // Memory ids
+    pub memories: Vec<&'a str>,
+    pub eras: Vec<&'a str>,
+    pub directions: Vec<&'a str>,
+    pub templates: Vec<&'a str>,
+    pub prompts: Vec<&'a Prompt>,
+    // Room ids along with the id of an interactable spawned in them
+    pub spawned: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> References<'a> {
+    pub fn collect(conditions: &'a [Condition], actions: &'a [Action]) -> Self {
+        let mut references = References::default();
+
+        for condition in conditions {
+            condition.collect_references(&mut references);
+        }
+
+        // Prompts carry actions of their own, which are checked along with the rest
+        let mut actions: Vec<&Action> = actions.iter().collect();
+        let mut next = 0;
+        while next < actions.len() {
+            let action = actions[next];
+            next += 1;
+
+            match action {
+                Action::Print { text } => references.templates.push(text),
+                Action::UnlockExit { room, direction } | Action::LockExit { room, direction } => {
+                    references.rooms.push(room);
+                    references.directions.push(direction);
+                }
+                Action::SpawnInteractable { room, interactable } => {
+                    references.rooms.push(room);
+                    references.spawned.push((room, &interactable.id));
+                    if !interactable.prerequisite_item.is_empty() {
+                        references.items.push(&interactable.prerequisite_item);
+                    }
+                }
+                Action::LightRoom { room } | Action::DarkenRoom { room } => references.rooms.push(room),
+                Action::SetRoomDescription { room, description } => {
+                    references.rooms.push(room);
+                    references.templates.push(description);
+                }
+                Action::MoveItem { item, to } => {
+                    references.items.push(item);
+                    match to {
+                        ItemDestination::Room(room) => references.rooms.push(room),
+                        ItemDestination::Container(container) => references.items.push(container),
+                        _ => {}
+                    }
+                }
+                Action::Win { ending } => references.endings.push(ending),
+                Action::Lose { reason } => references.templates.push(reason),
+                Action::Ask { prompt } => {
+                    references.prompts.push(prompt);
+                    references.templates.push(&prompt.question);
+                    for choice in &prompt.choices {
+                        references.templates.push(&choice.text);
+                        actions.extend(&choice.actions);
+                    }
+                }
+                Action::Talk { npc, node } => {
+                    references.npcs.push(npc);
+                    references.dialogue.push((npc, node));
+                }
+                Action::Remember { memory } => references.memories.push(memory),
+                Action::ChangeEra { era } => references.eras.push(era),
+                _ => {}
+            }
+        }
+
+        references
+    }
+}
+
+pub fn run_action(game_state: &mut GameState, action: &Action, outcome: &mut Outcome) {
+    match action {
+        Action::Print { text } => outcome.narrate(&render(text, game_state)),
+        Action::SetFlag { flag } => {
+            if game_state.flags.insert(flag.clone()) {
+                outcome.event(GameEvent::FlagSet(flag.clone()));
+            }
+        }
+        Action::ClearFlag { flag } => {
+            game_state.flags.remove(flag);
+        }
+        Action::SetVariable { name, value } => {
+            game_state.variables.insert(name.clone(), *value);
+        }
+        Action::AddToVariable { name, amount } => {
+            *game_state.variables.entry(name.clone()).or_insert(0) += amount;
+        }
+        Action::UnlockExit { room, direction } | Action::LockExit { room, direction } => {
+            let locked = matches!(action, Action::LockExit { .. });
+            let room_idx = match game_state.find_room(room) {
+                Some(room_idx) => room_idx,
+                None => return,
+            };
+            let direction = match text_to_direction(direction) {
+                Some(direction) => direction,
+                None => return,
+            };
+
+            for exit in game_state.rooms[room_idx]
+                .exits
+                .iter_mut()
+                .filter(|x| x.direction == direction && x.is_locked() != locked)
+            {
+                if locked {
+                    exit.lock();
+                } else {
+                    exit.unlock();
+                    outcome.event(GameEvent::ExitUnlocked {
+                        room_idx,
+                        direction: direction.clone(),
+                    });
+                }
+            }
+        }
+        Action::MoveItem { item, to } => {
+            let room_idx = match to {
+                ItemDestination::Room(room) => game_state.find_room(room),
+                _ => None,
+            };
+
+            if let Some(item) = game_state.inventory.get_mut(item) {
+                match to {
+                    ItemDestination::Inventory => item.move_to_inventory(),
+                    ItemDestination::Nowhere => item.move_to_nowhere(),
+                    ItemDestination::Room(_) => {
+                        if let Some(room_idx) = room_idx {
+                            item.move_to_room(room_idx);
+                        }
+                    }
+                    ItemDestination::Container(container) => item.move_to_container(container),
+                }
+            }
+        }
+        Action::SpawnInteractable { room, interactable } => {
+            if let Some(room_idx) = game_state.find_room(room) {
+                let interactables = &mut game_state.rooms[room_idx].interactables;
+                if !interactables.iter().any(|x| x.id == interactable.id) {
+                    interactables.push(interactable.as_ref().clone());
+                }
+            }
+        }
+        Action::SetRoomDescription { room, description } => {
+            if let Some(room_idx) = game_state.find_room(room) {
+                game_state.rooms[room_idx].description = description.clone();
+            }
+        }
+        Action::LightRoom { room } | Action::DarkenRoom { room } => {
+            let dark = matches!(action, Action::DarkenRoom { .. });
+            if let Some(room_idx) = game_state.find_room(room) {
+                game_state.rooms[room_idx].dark = dark;
+            }
+        }
+        Action::Win { ending } => {
+            game_state.status = GameStatus::Won(ending.clone());
+            if let Some(text) = game_state.ending().map(|x| x.text.clone()) {
+                outcome.narrate(&render(&text, game_state));
+            }
+        }
+        Action::Lose { reason } => {
+            let reason = render(reason, game_state);
+            outcome.narrate(&reason);
+            game_state.status = GameStatus::Lost(reason);
+        }
+        Action::Ask { prompt } => ask(game_state, prompt, outcome),
+        Action::Talk { npc, node } => say(game_state, npc, node, outcome),
+        Action::Remember { memory } => enter_memory(game_state, memory, outcome),
+        Action::ChangeEra { era } => change_era(game_state, era, outcome),
+    }
+}
+
+// Fires every trigger that accepts the event, returning false once the per-turn cap is hit
+fn fire_triggers<F>(game_state: &mut GameState, outcome: &mut Outcome, fired: &mut usize, accepts: F) -> bool
+where
+    F: Fn(&GameState, &Trigger) -> bool,
+{
+    for idx in 0..game_state.triggers.len() {
+        let trigger = &game_state.triggers[idx];
+        if !trigger.can_fire()
+            || !accepts(game_state, trigger)
+            || !trigger.conditions.iter().all(|x| x.holds(game_state))
+        {
+            continue;
+        }
+
+        if *fired >= MAX_TRIGGERS_PER_TURN {
+            return false;
+        }
+        *fired += 1;
+
+        game_state.triggers[idx].fired = true;
+        for action in game_state.triggers[idx].actions.clone() {
+            run_action(game_state, &action, outcome);
+        }
+    }
+
+    true
+}
+
+/// Runs the triggers for everything that happened this turn. Events raised by the actions of a
+/// trigger can fire further triggers.
+pub fn run_triggers(game_state: &mut GameState, outcome: &mut Outcome, turn_passed: bool) {
+    let mut fired = 0;
+
+    if turn_passed {
+        let turn = game_state.turn;
+        let accepts = |_: &GameState, trigger: &Trigger| trigger.event == TriggerEvent::Turn { count: turn };
+        if !fire_triggers(game_state, outcome, &mut fired, accepts) {
+            return;
+        }
+    }
+
+    let mut next_event = 0;
+    while next_event < outcome.events.len() {
+        let event = outcome.events[next_event].clone();
+        next_event += 1;
+
+        let accepts = |game_state: &GameState, trigger: &Trigger| trigger.event.matches(game_state, &event);
+        if !fire_triggers(game_state, outcome, &mut fired, accepts) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_trigger_game_state(triggers: Vec<Trigger>) -> GameState {
+        let mut game_state = start_game();
+        game_state.triggers = triggers;
+        game_state
+    }
+
+    fn trigger(event: TriggerEvent, conditions: Vec<Condition>, actions: Vec<Action>) -> Trigger {
+        Trigger {
+            id: "test".to_string(),
+            event,
+            conditions,
+            actions,
+            repeat: false,
+            fired: false,
+        }
+    }
+
+    #[test]
+    fn test_enter_room_trigger() {
+        let game_state = create_trigger_game_state(vec![trigger(
+            TriggerEvent::EnterRoom {
+                room: "north_door_room".to_string(),
+            },
+            vec![],
+            vec![
+                Action::Print {
+                    text: "Dust swirls around your feet".to_string(),
+                },
+                Action::SetFlag {
+                    flag: "entered_lab".to_string(),
+                },
+            ],
+        )]);
+
+        let unlocked_state = update(game_state, "use pendant".to_string());
+        let entered_state = update(unlocked_state, "go south".to_string());
+
+        assert!(entered_state.sys_message.ends_with("Dust swirls around your feet"));
+        assert!(entered_state.flags.contains("entered_lab"));
+        assert!(entered_state
+            .outcome
+            .events
+            .contains(&GameEvent::FlagSet("entered_lab".to_string())));
+
+        let left_state = update(entered_state, "go north".to_string());
+        let again_state = update(left_state, "go south".to_string());
+
+        assert!(!again_state.sys_message.contains("Dust swirls"));
+    }
+
+    #[test]
+    fn test_lose_with_conditional_reason() {
+        let game_state = create_trigger_game_state(vec![trigger(
+            TriggerEvent::UseItem {
+                item: "pendant".to_string(),
+            },
+            vec![],
+            vec![Action::Lose {
+                reason: "The door crushes you{if has helmet}, helmet and all{else} before you can react{end}."
+                    .to_string(),
+            }],
+        )]);
+
+        let lost_state = update(game_state, "use pendant".to_string());
+
+        assert_eq!(
+            lost_state.status,
+            GameStatus::Lost("The door crushes you before you can react.".to_string())
+        );
+        assert!(lost_state
+            .sys_message
+            .ends_with("\nThe door crushes you before you can react."));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let game_state = create_trigger_game_state(vec![trigger(
+            TriggerEvent::Turn { count: 1 },
+            vec![Condition::Not {
+                condition: Box::new(Condition::HasItem {
+                    item: "pendant".to_string(),
+                }),
+            }],
+            vec![Action::Print {
+                text: "You feel lighter".to_string(),
+            }],
+        )]);
+
+        let kept_state = update(game_state.clone(), "show inventory".to_string());
+
+        assert!(!kept_state.sys_message.contains("You feel lighter"));
+
+        let dropped_state = update(game_state, "drop pendant".to_string());
+
+        assert_eq!(dropped_state.sys_message, "You drop the pendant\nYou feel lighter");
+    }
+
+    #[test]
+    fn test_flag_set_chains_triggers() {
+        let game_state = create_trigger_game_state(vec![
+            trigger(
+                TriggerEvent::UseItem {
+                    item: "pendant".to_string(),
+                },
+                vec![],
+                vec![Action::SetFlag {
+                    flag: "door_open".to_string(),
+                }],
+            ),
+            trigger(
+                TriggerEvent::FlagSet {
+                    flag: "door_open".to_string(),
+                },
+                vec![Condition::Visited {
+                    room: "ruin_entrance".to_string(),
+                }],
+                vec![
+                    Action::MoveItem {
+                        item: "buster".to_string(),
+                        to: ItemDestination::Room("ruin_entrance".to_string()),
+                    },
+                    Action::LockExit {
+                        room: "ruin_entrance".to_string(),
+                        direction: "south".to_string(),
+                    },
+                    Action::Win {
+                        ending: "early".to_string(),
+                    },
+                ],
+            ),
+        ]);
+
+        let after_state = update(game_state, "use pendant".to_string());
+
+        assert!(after_state.inventory.get("buster").unwrap().is_in_room(0));
+        assert!(after_state.rooms[0].exits[0].is_locked());
+        assert_eq!(after_state.status, GameStatus::Won("early".to_string()));
+
+        let over_state = update(after_state, "go south".to_string());
+
+        assert_eq!(over_state.sys_message, "The game is over.");
+    }
+
+    #[test]
+    fn test_repeating_triggers_are_capped() {
+        let mut looping = trigger(
+            TriggerEvent::FlagSet {
+                flag: "a".to_string(),
+            },
+            vec![],
+            vec![
+                Action::Print {
+                    text: "The lights flicker".to_string(),
+                },
+                Action::ClearFlag {
+                    flag: "a".to_string(),
+                },
+                Action::SetFlag {
+                    flag: "a".to_string(),
+                },
+            ],
+        );
+        looping.repeat = true;
+
+        let mut game_state = create_trigger_game_state(vec![looping.clone()]);
+        let mut outcome = Outcome::default();
+        outcome.event(GameEvent::FlagSet("a".to_string()));
+
+        run_triggers(&mut game_state, &mut outcome, false);
+
+        assert_eq!(outcome.message().matches("The lights flicker").count(), MAX_TRIGGERS_PER_TURN);
+        assert!(game_state.flags.contains("a"));
+
+        let mut start = trigger(
+            TriggerEvent::Turn { count: 1 },
+            vec![],
+            vec![Action::SetFlag {
+                flag: "a".to_string(),
+            }],
+        );
+        start.id = "start".to_string();
+
+        let game_state = create_trigger_game_state(vec![start, looping]);
+
+        let after_state = update(game_state, "show inventory".to_string());
+
+        // The turn itself still comes back once the triggers are cut off
+        assert!(after_state.sys_message.starts_with("Your inventory:"));
+        assert_eq!(after_state.status, GameStatus::Playing);
+    }
+
+    #[test]
+    fn test_spawn_interactable_and_describe_room() {
+        let game_state = create_trigger_game_state(vec![trigger(
+            TriggerEvent::Interact {
+                interactable: "lab_entrance".to_string(),
+            },
+            vec![],
+            vec![
+                Action::SpawnInteractable {
+                    room: "ruin_entrance".to_string(),
+                    interactable: Box::new(Interactable {
+                        id: "panel".to_string(),
+                        name: "panel".to_string(),
+                        before_interaction_description: "A glowing panel".to_string(),
+                        ..Default::default()
+                    }),
+                },
+                Action::SetRoomDescription {
+                    room: "ruin_entrance".to_string(),
+                    description: "The door stands open".to_string(),
+                },
+            ],
+        )]);
+
+        let after_state = update(game_state, "use pendant on door".to_string());
+        let examined_state = update(after_state, "examine panel".to_string());
+
+        assert_eq!(examined_state.sys_message, "A glowing panel");
+        assert_eq!(
+            examined_state.describe_room(0),
+            "The door stands open\nOn the ground you see: pendant\nAlso here: robot"
+        );
+    }
+}
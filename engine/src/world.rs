@@ -0,0 +1,1143 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_derive::Deserialize;
+
+use super::*;
+
+pub const WORLD_FORMAT_VERSION: u32 = 1;
+
+pub static DEFAULT_WORLD: &str = include_str!("../worlds/default.json");
+
+#[derive(Debug, PartialEq)]
+pub enum WorldError {
+    ContainerCycle(String),
+    DuplicateEra { location: String, era: String },
+    DuplicateItem(String),
+    DuplicateRoom(String),
+    EmptyPrompt(String),
+    InvalidRecipe(String),
+    InvalidTemplate(String),
+    ItemPlacedTwice(String),
+    Parse(String),
+    UnsupportedVersion(u32),
+    UnknownDialogueNode { npc: String, node: String },
+    UnknownDirection(String),
+    UnknownEnding(String),
+    UnknownEra(String),
+    UnknownInteractable { room: String, interactable: String },
+    UnknownItem(String),
+    UnknownMemory(String),
+    UnknownNpc(String),
+    UnknownRoom(String),
+}
+
+impl fmt::Display for WorldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorldError::ContainerCycle(name) => {
+                write!(f, "The item `{}` ends up inside itself", name)
+            }
+            WorldError::DuplicateEra { location, era } => write!(
+                f,
+                "The location `{}` has more than one room in the era `{}`",
+                location, era
+            ),
+            WorldError::DuplicateItem(name) => {
+                write!(f, "More than one item is named `{}`", name)
+            }
+            WorldError::DuplicateRoom(id) => {
+                write!(f, "More than one room has the id `{}`", id)
+            }
+            WorldError::EmptyPrompt(question) => {
+                write!(f, "The question `{}` has no answers to choose from", question)
+            }
+            WorldError::InvalidRecipe(reason) => write!(f, "A recipe is invalid: {}", reason),
+            WorldError::InvalidTemplate(reason) => {
+                write!(f, "A description is not a valid template: {}", reason)
+            }
+            WorldError::ItemPlacedTwice(name) => {
+                write!(f, "The item `{}` is placed in more than one location", name)
+            }
+            WorldError::Parse(msg) => write!(f, "The world file could not be read: {}", msg),
+            WorldError::UnsupportedVersion(version) => write!(
+                f,
+                "World format version {} is not supported (expected {})",
+                version, WORLD_FORMAT_VERSION
+            ),
+            WorldError::UnknownDialogueNode { npc, node } => write!(
+                f,
+                "The npc `{}` has no dialogue node with the id `{}`",
+                npc, node
+            ),
+            WorldError::UnknownDirection(direction) => {
+                write!(f, "`{}` is not a known direction", direction)
+            }
+            WorldError::UnknownEnding(id) => write!(f, "No ending with the id `{}` is defined", id),
+            WorldError::UnknownEra(id) => write!(f, "No era with the id `{}` is defined", id),
+            WorldError::UnknownInteractable { room, interactable } => write!(
+                f,
+                "The room `{}` has no interactable with the id `{}`",
+                room, interactable
+            ),
+            WorldError::UnknownItem(name) => write!(f, "No item named `{}` is defined", name),
+            WorldError::UnknownMemory(id) => write!(f, "No memory with the id `{}` is defined", id),
+            WorldError::UnknownNpc(id) => write!(f, "No npc with the id `{}` is defined", id),
+            WorldError::UnknownRoom(id) => write!(f, "No room with the id `{}` is defined", id),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct World {
+    version: u32,
+    start_room: String,
+    rooms: Vec<RoomDef>,
+    #[serde(default)]
+    items: Vec<ItemDef>,
+    #[serde(default)]
+    inventory: Vec<String>,
+    #[serde(default)]
+    carry_limit: CarryLimit,
+    #[serde(default)]
+    recipes: Vec<Recipe>,
+    #[serde(default)]
+    triggers: Vec<Trigger>,
+    #[serde(default)]
+    endings: Vec<Ending>,
+    #[serde(default)]
+    npcs: Vec<NpcDef>,
+    #[serde(default)]
+    memories: Vec<Memory>,
+    // The ids of the eras rooms can belong to. The game starts in the first one
+    #[serde(default)]
+    eras: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RoomDef {
+    id: String,
+    description: String,
+    #[serde(default)]
+    equipped_descriptions: HashMap<String, String>,
+    #[serde(default)]
+    exits: Vec<ExitDef>,
+    #[serde(default)]
+    interactables: Vec<InteractableDef>,
+    #[serde(default)]
+    items: Vec<String>,
+    // The id of the room's place in the world, shared by its versions in other eras. Defaults to
+    // the id of the room
+    #[serde(default)]
+    location: String,
+    #[serde(default)]
+    era: String,
+    #[serde(default)]
+    dark: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpcDef {
+    id: String,
+    name: String,
+    #[serde(default)]
+    adjectives: Vec<String>,
+    description: String,
+    // The id of the room the npc starts in
+    room: String,
+    #[serde(default)]
+    follows: bool,
+    #[serde(default)]
+    dialogue: Dialogue,
+    #[serde(default)]
+    orders: Vec<Order>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExitDef {
+    direction: String,
+    target: String,
+    #[serde(default)]
+    locked: bool,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default)]
+    revealed_by: String,
+    #[serde(default)]
+    interactable_id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    adjectives: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InteractableDef {
+    id: String,
+    name: String,
+    #[serde(default)]
+    adjectives: Vec<String>,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default)]
+    revealed_by: String,
+    before_interaction_description: String,
+    interaction_description: String,
+    after_interaction_description: String,
+    #[serde(default)]
+    equipped_descriptions: HashMap<String, String>,
+    #[serde(default)]
+    prerequisite_item: String,
+    #[serde(default)]
+    memory: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemDef {
+    name: String,
+    #[serde(default)]
+    adjectives: Vec<String>,
+    description: String,
+    #[serde(default)]
+    hidden: bool,
+    // An item that has to be carried for a search to find this one
+    #[serde(default)]
+    revealed_by: String,
+    #[serde(default)]
+    slot: Option<Slot>,
+    #[serde(default)]
+    fixed: bool,
+    #[serde(default)]
+    container: Option<ContainerDef>,
+    #[serde(default)]
+    light: Option<Light>,
+    #[serde(default)]
+    weight: u32,
+    #[serde(default)]
+    bulk: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerDef {
+    #[serde(default)]
+    open: bool,
+    #[serde(default)]
+    locked: bool,
+    #[serde(default)]
+    key: Option<String>,
+    // The names of the items that start out inside the container
+    #[serde(default)]
+    contents: Vec<String>,
+}
+
+// Puts an item at its starting location, making sure that no item is placed twice
+fn place_item(
+    inventory: &mut HashMap<String, Item>,
+    name: &str,
+    location: ItemState,
+) -> Result<(), WorldError> {
+    match inventory.get_mut(name) {
+        Some(item) if *item.get_location() == ItemState::Nowhere => {
+            item.location = location;
+            Ok(())
+        }
+        Some(_) => Err(WorldError::ItemPlacedTwice(name.to_string())),
+        None => Err(WorldError::UnknownItem(name.to_string())),
+    }
+}
+
+// Checks that every item named by equipped_descriptions exists in the world
+fn check_equipped_descriptions(
+    equipped_descriptions: &HashMap<String, String>,
+    inventory: &HashMap<String, Item>,
+) -> Result<(), WorldError> {
+    match equipped_descriptions
+        .keys()
+        .find(|name| !inventory.contains_key(name.as_str()))
+    {
+        Some(name) => Err(WorldError::UnknownItem(name.clone())),
+        None => Ok(()),
+    }
+}
+
+// Checks an optional item name, such as the item that a hidden thing is revealed by
+fn check_item(name: &str, inventory: &HashMap<String, Item>) -> Result<(), WorldError> {
+    if name.is_empty() || inventory.contains_key(name) {
+        Ok(())
+    } else {
+        Err(WorldError::UnknownItem(name.to_string()))
+    }
+}
+
+fn concealment(hidden: bool, revealed_by: String) -> Concealment {
+    Concealment {
+        hidden,
+        found: false,
+        revealed_by,
+    }
+}
+
+// Checks that a memory an interactable or a trigger takes the player into is defined
+fn check_memory(id: &str, memories: &[Memory]) -> Result<(), WorldError> {
+    if memories.iter().any(|x| x.id == id) {
+        Ok(())
+    } else {
+        Err(WorldError::UnknownMemory(id.to_string()))
+    }
+}
+
+// Checks that a room or a trigger names one of the eras of the world
+fn check_era(id: &str, eras: &[String]) -> Result<(), WorldError> {
+    if eras.iter().any(|x| x == id) {
+        Ok(())
+    } else {
+        Err(WorldError::UnknownEra(id.to_string()))
+    }
+}
+
+// Checks that everything a trigger or a dialogue option refers to is defined in the world
+fn check_references(
+    references: &References,
+    room_indices: &HashMap<&str, usize>,
+    inventory: &HashMap<String, Item>,
+    npcs: &[NpcDef],
+    endings: &[Ending],
+    memories: &[Memory],
+    eras: &[String],
+) -> Result<(), WorldError> {
+    if let Some(id) = references.rooms.iter().find(|x| !room_indices.contains_key(**x)) {
+        return Err(WorldError::UnknownRoom(id.to_string()));
+    }
+    if let Some(name) = references.items.iter().find(|x| !inventory.contains_key(**x)) {
+        return Err(WorldError::UnknownItem(name.to_string()));
+    }
+    if let Some(id) = references.npcs.iter().find(|x| !npcs.iter().any(|npc| npc.id == **x)) {
+        return Err(WorldError::UnknownNpc(id.to_string()));
+    }
+    if let Some((npc, node)) = references.dialogue.iter().find(|(npc, node)| {
+        !npcs
+            .iter()
+            .any(|x| x.id == *npc && x.dialogue.nodes.contains_key(*node))
+    }) {
+        return Err(WorldError::UnknownDialogueNode {
+            npc: npc.to_string(),
+            node: node.to_string(),
+        });
+    }
+    if let Some(direction) = references.directions.iter().find(|x| !is_direction(x)) {
+        return Err(WorldError::UnknownDirection(direction.to_string()));
+    }
+    if let Some(id) = references.endings.iter().find(|x| !endings.iter().any(|ending| ending.id == **x)) {
+        return Err(WorldError::UnknownEnding(id.to_string()));
+    }
+    if let Some(prompt) = references.prompts.iter().find(|x| x.choices.is_empty()) {
+        return Err(WorldError::EmptyPrompt(prompt.question.clone()));
+    }
+    for id in &references.memories {
+        check_memory(id, memories)?;
+    }
+    for id in &references.eras {
+        check_era(id, eras)?;
+    }
+
+    Ok(())
+}
+
+// Checks every description in the world that can use the template syntax
+fn check_templates(game_state: &GameState) -> Result<(), WorldError> {
+    let mut texts: Vec<&str> = vec![];
+
+    for room in &game_state.rooms {
+        texts.push(&room.description);
+        texts.extend(room.equipped_descriptions.values().map(String::as_str));
+
+        for x in &room.interactables {
+            texts.push(&x.before_interaction_description);
+            texts.push(&x.interaction_description);
+            texts.push(&x.after_interaction_description);
+            texts.extend(x.equipped_descriptions.values().map(String::as_str));
+        }
+    }
+    texts.extend(game_state.inventory.values().map(Item::get_description));
+    for trigger in &game_state.triggers {
+        texts.extend(trigger.references().templates);
+    }
+    texts.extend(game_state.endings.iter().map(|x| x.text.as_str()));
+    for npc in &game_state.npcs {
+        texts.push(&npc.description);
+
+        for node in npc.dialogue.nodes.values() {
+            texts.push(&node.text);
+
+            for option in &node.options {
+                texts.push(&option.text);
+                texts.extend(References::collect(&option.conditions, &option.actions).templates);
+            }
+        }
+
+        for order in &npc.orders {
+            texts.push(&order.refusal);
+            texts.extend(References::collect(&order.conditions, &order.actions).templates);
+        }
+    }
+    for memory in &game_state.memories {
+        texts.push(&memory.description);
+        texts.push(&memory.closing);
+
+        for command in &memory.commands {
+            texts.push(&command.text);
+            texts.extend(References::collect(&[], &command.actions).templates);
+        }
+    }
+
+    for text in texts {
+        check_template(text).map_err(WorldError::InvalidTemplate)?;
+    }
+
+    Ok(())
+}
+
+/// Builds the initial game state from a world file in the JSON world format.
+pub fn load_world(world_str: &str) -> Result<GameState, WorldError> {
+    let world: World =
+        serde_json::from_str(world_str).map_err(|e| WorldError::Parse(e.to_string()))?;
+
+    if world.version != WORLD_FORMAT_VERSION {
+        return Err(WorldError::UnsupportedVersion(world.version));
+    }
+
+    let mut room_indices: HashMap<&str, usize> = HashMap::new();
+    for (idx, room) in world.rooms.iter().enumerate() {
+        if room_indices.insert(room.id.as_str(), idx).is_some() {
+            return Err(WorldError::DuplicateRoom(room.id.clone()));
+        }
+    }
+
+    let room_idx = |id: &str| {
+        room_indices
+            .get(id)
+            .cloned()
+            .ok_or_else(|| WorldError::UnknownRoom(id.to_string()))
+    };
+
+    let mut inventory = HashMap::new();
+    let mut contents = vec![];
+    for item in world.items {
+        let name = item.name.clone();
+        let container = item.container.map(|container| {
+            contents.push((name, container.contents));
+
+            Container {
+                open: container.open,
+                locked: container.locked,
+                key: container.key,
+            }
+        });
+
+        if inventory.contains_key(item.name.as_str()) {
+            return Err(WorldError::DuplicateItem(item.name));
+        }
+
+        inventory.insert(
+            item.name.clone(),
+            Item {
+                adjectives: item.adjectives,
+                name: item.name,
+                description: item.description,
+                location: ItemState::Nowhere,
+                slot: item.slot,
+                container,
+                light: item.light,
+                fixed: item.fixed,
+                weight: item.weight,
+                bulk: item.bulk,
+                concealment: concealment(item.hidden, item.revealed_by),
+            },
+        );
+    }
+
+    for name in &world.inventory {
+        place_item(&mut inventory, name, ItemState::Inventory)?;
+    }
+
+    for (container_name, names) in &contents {
+        for name in names {
+            place_item(&mut inventory, name, ItemState::Container(container_name.clone()))?;
+        }
+    }
+
+    for item in inventory.values() {
+        if let Some(key) = item.get_container().and_then(Container::get_key) {
+            if !inventory.contains_key(key) {
+                return Err(WorldError::UnknownItem(key.to_string()));
+            }
+        }
+        check_item(&item.concealment.revealed_by, &inventory)?;
+    }
+
+    let mut rooms = Vec::with_capacity(world.rooms.len());
+    for (idx, room) in world.rooms.iter().enumerate() {
+        let mut exits = Vec::with_capacity(room.exits.len());
+        for exit in &room.exits {
+            exits.push(Exit {
+                direction: text_to_direction(&exit.direction)
+                    .ok_or_else(|| WorldError::UnknownDirection(exit.direction.clone()))?,
+                locked: exit.locked,
+                interactable_id: exit.interactable_id.clone(),
+                target: room_idx(&exit.target)?,
+                concealment: concealment(exit.hidden, exit.revealed_by.clone()),
+                name: exit.name.clone(),
+                adjectives: exit.adjectives.clone(),
+            });
+        }
+
+        for name in &room.items {
+            place_item(&mut inventory, name, ItemState::Room(idx))?;
+        }
+
+        check_equipped_descriptions(&room.equipped_descriptions, &inventory)?;
+        for x in &room.interactables {
+            check_equipped_descriptions(&x.equipped_descriptions, &inventory)?;
+            check_item(&x.revealed_by, &inventory)?;
+            check_item(&x.prerequisite_item, &inventory)?;
+            if !x.memory.is_empty() {
+                check_memory(&x.memory, &world.memories)?;
+            }
+        }
+        for exit in &room.exits {
+            check_item(&exit.revealed_by, &inventory)?;
+        }
+
+        let location = if room.location.is_empty() {
+            room.id.clone()
+        } else {
+            room.location.clone()
+        };
+        if !room.era.is_empty() {
+            check_era(&room.era, &world.eras)?;
+
+            if rooms.iter().any(|x: &Room| x.location == location && x.era == room.era) {
+                return Err(WorldError::DuplicateEra {
+                    location,
+                    era: room.era.clone(),
+                });
+            }
+        }
+
+        rooms.push(Room {
+            id: room.id.clone(),
+            description: room.description.clone(),
+            equipped_descriptions: room.equipped_descriptions.clone(),
+            exits,
+            interactables: room
+                .interactables
+                .iter()
+                .map(|x| Interactable {
+                    adjectives: x.adjectives.clone(),
+                    after_interaction_description: x.after_interaction_description.clone(),
+                    before_interaction_description: x.before_interaction_description.clone(),
+                    concealment: concealment(x.hidden, x.revealed_by.clone()),
+                    equipped_descriptions: x.equipped_descriptions.clone(),
+                    id: x.id.clone(),
+                    interaction_description: x.interaction_description.clone(),
+                    interacted: false,
+                    memory: x.memory.clone(),
+                    name: x.name.clone(),
+                    prerequisite_item: x.prerequisite_item.clone(),
+                })
+                .collect(),
+            location,
+            era: room.era.clone(),
+            dark: room.dark,
+            visits: 0,
+        });
+    }
+
+    // Interactables that actions spawn, which exits can depend on as well
+    let mut spawned: Vec<(&str, &str)> = vec![];
+
+    for trigger in &world.triggers {
+        let references = trigger.references();
+        spawned.extend(&references.spawned);
+        check_references(
+            &references,
+            &room_indices,
+            &inventory,
+            &world.npcs,
+            &world.endings,
+            &world.memories,
+            &world.eras,
+        )?;
+    }
+
+    for npc in &world.npcs {
+        room_idx(&npc.room)?;
+
+        let dialogue = &npc.dialogue;
+        let mut nodes: Vec<&str> = vec![];
+        if !dialogue.start.is_empty() {
+            nodes.push(&dialogue.start);
+        }
+
+        for node in dialogue.nodes.values() {
+            for option in &node.options {
+                let references = References::collect(&option.conditions, &option.actions);
+                spawned.extend(&references.spawned);
+                check_references(
+                    &references,
+                    &room_indices,
+                    &inventory,
+                    &world.npcs,
+                    &world.endings,
+                    &world.memories,
+                    &world.eras,
+                )?;
+                nodes.extend(option.next.as_deref());
+            }
+        }
+
+        for order in &npc.orders {
+            let references = References::collect(&order.conditions, &order.actions);
+            spawned.extend(&references.spawned);
+            check_references(
+                &references,
+                &room_indices,
+                &inventory,
+                &world.npcs,
+                &world.endings,
+                &world.memories,
+                &world.eras,
+            )?;
+        }
+
+        if let Some(node) = nodes.iter().find(|x| !dialogue.nodes.contains_key(**x)) {
+            return Err(WorldError::UnknownDialogueNode {
+                npc: npc.id.clone(),
+                node: node.to_string(),
+            });
+        }
+    }
+
+    for memory in &world.memories {
+        for command in &memory.commands {
+            let references = References::collect(&[], &command.actions);
+            spawned.extend(&references.spawned);
+            check_references(
+                &references,
+                &room_indices,
+                &inventory,
+                &world.npcs,
+                &world.endings,
+                &world.memories,
+                &world.eras,
+            )?;
+        }
+    }
+
+    // Using the prerequisite item of an exit's interactable unlocks the exit, so the interactable
+    // has to be in the same room
+    for room in &world.rooms {
+        for exit in room.exits.iter().filter(|x| !x.interactable_id.is_empty()) {
+            let id = exit.interactable_id.as_str();
+            if !room.interactables.iter().any(|x| x.id == id) && !spawned.contains(&(&room.id, id)) {
+                return Err(WorldError::UnknownInteractable {
+                    room: room.id.clone(),
+                    interactable: id.to_string(),
+                });
+            }
+        }
+    }
+
+    for recipe in &world.recipes {
+        if recipe.ingredients.len() != 2 || recipe.ingredients[0] == recipe.ingredients[1] {
+            return Err(WorldError::InvalidRecipe(format!(
+                "`{}` must combine two different items",
+                recipe.ingredients.join(", ")
+            )));
+        }
+
+        for name in recipe.ingredients.iter().chain(&recipe.result) {
+            if !inventory.contains_key(name.as_str()) {
+                return Err(WorldError::UnknownItem(name.clone()));
+            }
+        }
+
+        if let Some(name) = recipe.keep.iter().find(|x| !recipe.ingredients.contains(x)) {
+            return Err(WorldError::InvalidRecipe(format!(
+                "`{}` is kept but is not an ingredient",
+                name
+            )));
+        }
+    }
+
+    // Containers that hold each other would leave their contents out of reach forever
+    if let Some((name, _)) = contents.iter().find(|(name, _)| {
+        let mut location = inventory.get(name.as_str()).map(Item::get_location);
+        for _ in 0..inventory.len() {
+            match location {
+                Some(ItemState::Container(parent)) => {
+                    location = inventory.get(parent.as_str()).map(Item::get_location)
+                }
+                _ => return false,
+            }
+        }
+        true
+    }) {
+        return Err(WorldError::ContainerCycle(name.clone()));
+    }
+
+    let mut npcs = Vec::with_capacity(world.npcs.len());
+    for npc in world.npcs {
+        npcs.push(Npc {
+            room_idx: room_idx(&npc.room)?,
+            id: npc.id,
+            name: npc.name,
+            adjectives: npc.adjectives,
+            description: npc.description,
+            follows: npc.follows,
+            dialogue: npc.dialogue,
+            orders: npc.orders,
+        });
+    }
+
+    let start_room_idx = room_idx(&world.start_room)?;
+    rooms[start_room_idx].visits = 1;
+
+    let game_state = GameState {
+        current_room_idx: start_room_idx,
+        inventory,
+        carry_limit: world.carry_limit,
+        recipes: world.recipes,
+        flags: BTreeSet::new(),
+        variables: BTreeMap::new(),
+        turn: 0,
+        triggers: world.triggers,
+        endings: world.endings,
+        npcs,
+        memories: world.memories,
+        memories_seen: BTreeSet::new(),
+        scene: None,
+        era: world.eras.first().cloned().unwrap_or_default(),
+        status: GameStatus::Playing,
+        prompt: None,
+        rooms,
+        sys_message: "".to_string(),
+        system_request: None,
+        outcome: Outcome::default(),
+    };
+
+    check_templates(&game_state)?;
+
+    Ok(game_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_default_world() {
+        let game_state = load_world(DEFAULT_WORLD).unwrap();
+
+        assert_eq!(game_state.current_room_idx, 0);
+        assert_eq!(game_state.rooms.len(), 8);
+        assert_eq!(game_state.era, "present");
+        assert_eq!(game_state.rooms[0].exits[0].target, 2);
+        assert!(game_state.rooms[0].exits[0].is_locked());
+        assert_eq!(game_state.inventory.get("helmet").unwrap().get_location(), &ItemState::Room(1));
+        assert!(game_state.inventory.get("pendant").unwrap().is_in_inventory());
+        assert!(!game_state.inventory.get("helmet").unwrap().is_in_inventory());
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let world = r#"{"version": 99, "start_room": "a", "rooms": []}"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnsupportedVersion(99)
+        );
+    }
+
+    #[test]
+    fn test_unknown_exit_target() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [
+                {"id": "a", "description": "Room A", "exits": [{"direction": "north", "target": "b"}]}
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownRoom("b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_item_placed_twice() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [
+                {"id": "a", "description": "Room A", "items": ["key"]},
+                {"id": "b", "description": "Room B", "items": ["key"]}
+            ],
+            "items": [{"name": "key", "description": "A key"}]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::ItemPlacedTwice("key".to_string())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_room() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [
+                {"id": "a", "description": "Room A"},
+                {"id": "a", "description": "Another room A"}
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::DuplicateRoom("a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_item() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A"}],
+            "items": [
+                {"name": "coin", "description": "A coin"},
+                {"name": "coin", "description": "Another coin"}
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::DuplicateItem("coin".to_string())
+        );
+    }
+
+    #[test]
+    fn test_exit_with_unknown_interactable() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [
+                {
+                    "id": "a",
+                    "description": "Room A",
+                    "exits": [{"direction": "north", "target": "b", "locked": true, "interactable_id": "lever"}]
+                },
+                {
+                    "id": "b",
+                    "description": "Room B",
+                    "interactables": [{
+                        "id": "lever",
+                        "name": "lever",
+                        "before_interaction_description": "A lever",
+                        "interaction_description": "You pull the lever",
+                        "after_interaction_description": "A pulled lever"
+                    }]
+                }
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownInteractable {
+                room: "a".to_string(),
+                interactable: "lever".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_exit_with_spawned_interactable() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [
+                {
+                    "id": "a",
+                    "description": "Room A",
+                    "exits": [{"direction": "north", "target": "a", "locked": true, "interactable_id": "lever"}]
+                }
+            ],
+            "triggers": [{
+                "id": "spawn_lever",
+                "event": {"type": "turn", "count": 1},
+                "actions": [{
+                    "type": "spawn_interactable",
+                    "room": "a",
+                    "interactable": {
+                        "id": "lever",
+                        "name": "lever",
+                        "before_interaction_description": "A lever",
+                        "interaction_description": "You pull the lever",
+                        "after_interaction_description": "A pulled lever"
+                    }
+                }]
+            }]
+        }"#;
+
+        assert!(load_world(world).is_ok());
+    }
+
+    #[test]
+    fn test_interactable_with_unknown_prerequisite_item() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{
+                "id": "a",
+                "description": "Room A",
+                "interactables": [{
+                    "id": "lock",
+                    "name": "lock",
+                    "before_interaction_description": "A lock",
+                    "interaction_description": "The lock clicks open",
+                    "after_interaction_description": "An open lock",
+                    "prerequisite_item": "key"
+                }]
+            }],
+            "items": [{"name": "coin", "description": "A coin"}]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownItem("key".to_string())
+        );
+    }
+
+    #[test]
+    fn test_container_contents() {
+        let game_state = load_world(DEFAULT_WORLD).unwrap();
+
+        assert!(game_state.inventory.get("drawer").unwrap().is_in_container("desk"));
+        assert!(game_state.inventory.get("disk").unwrap().is_in_container("drawer"));
+        assert!(game_state.inventory.get("desk").unwrap().is_fixed());
+    }
+
+    #[test]
+    fn test_recipe_with_unknown_result() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A"}],
+            "items": [
+                {"name": "glue", "description": "Glue"},
+                {"name": "paper", "description": "Paper"}
+            ],
+            "recipes": [
+                {"ingredients": ["glue", "paper"], "result": "kite", "message": "You make a kite"}
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownItem("kite".to_string())
+        );
+    }
+
+    #[test]
+    fn test_trigger_with_unknown_room() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A"}],
+            "triggers": [
+                {
+                    "id": "welcome",
+                    "event": {"type": "enter_room", "room": "b"},
+                    "actions": [{"type": "print", "text": "Welcome"}]
+                }
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownRoom("b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_prompt_without_choices() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A"}],
+            "triggers": [
+                {
+                    "id": "question",
+                    "event": {"type": "turn", "count": 1},
+                    "actions": [{"type": "ask", "prompt": {"question": "Are you sure?", "choices": []}}]
+                }
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::EmptyPrompt("Are you sure?".to_string())
+        );
+    }
+
+    #[test]
+    fn test_trigger_with_unknown_ending() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A"}],
+            "endings": [{"id": "escape", "text": "You escape"}],
+            "triggers": [
+                {
+                    "id": "finale",
+                    "event": {"type": "turn", "count": 3},
+                    "actions": [{"type": "win", "ending": "rescue"}]
+                }
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownEnding("rescue".to_string())
+        );
+    }
+
+    #[test]
+    fn test_npc_with_unknown_dialogue_node() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A"}],
+            "npcs": [
+                {
+                    "id": "guard",
+                    "name": "guard",
+                    "description": "A guard",
+                    "room": "a",
+                    "dialogue": {
+                        "start": "hello",
+                        "nodes": {
+                            "hello": {"text": "Hello", "options": [{"text": "Why?", "next": "why"}]}
+                        }
+                    }
+                }
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownDialogueNode {
+                npc: "guard".to_string(),
+                node: "why".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_interactable_with_unknown_memory() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{
+                "id": "a",
+                "description": "Room A",
+                "interactables": [{
+                    "id": "mirror",
+                    "name": "mirror",
+                    "before_interaction_description": "A mirror",
+                    "interaction_description": "You look into the mirror",
+                    "after_interaction_description": "A mirror",
+                    "memory": "childhood"
+                }]
+            }],
+            "memories": [{"id": "wedding", "description": "A wedding"}]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownMemory("childhood".to_string())
+        );
+    }
+
+    #[test]
+    fn test_location_with_duplicate_era() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "eras": ["present", "past"],
+            "rooms": [
+                {"id": "a", "description": "Room A", "era": "present"},
+                {"id": "b", "description": "Room B", "location": "a", "era": "past"},
+                {"id": "c", "description": "Room C", "location": "a", "era": "past"}
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::DuplicateEra {
+                location: "a".to_string(),
+                era: "past".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A{if flag lit} is bright"}]
+        }"#;
+
+        match load_world(world).unwrap_err() {
+            WorldError::InvalidTemplate(_) => {}
+            err => panic!("expected an invalid template, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_container_cycle() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [{"id": "a", "description": "Room A"}],
+            "items": [
+                {"name": "box", "description": "A box", "container": {"contents": ["bag"]}},
+                {"name": "bag", "description": "A bag", "container": {"contents": ["box"]}}
+            ]
+        }"#;
+
+        match load_world(world).unwrap_err() {
+            WorldError::ContainerCycle(_) => {}
+            err => panic!("expected a container cycle, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_unknown_direction() {
+        let world = r#"{
+            "version": 1,
+            "start_room": "a",
+            "rooms": [
+                {"id": "a", "description": "Room A", "exits": [{"direction": "sideways", "target": "a"}]}
+            ]
+        }"#;
+
+        assert_eq!(
+            load_world(world).unwrap_err(),
+            WorldError::UnknownDirection("sideways".to_string())
+        );
+    }
+}
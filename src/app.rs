@@ -1,38 +1,54 @@
 use serde_derive::{Deserialize, Serialize};
 use yew::events::IKeyboardEvent;
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
 use yew::services::ConsoleService;
 use yew::{html, Component, ComponentLink, Html, Renderable, ShouldRender};
 
 extern crate engine;
 use engine::*;
 
+const SAVE_KEY: &'static str = "rust-text-adventure.save";
+
 #[derive(Serialize, Debug, Deserialize, Eq, PartialEq)]
 enum Author {
     System,
     Player,
 }
 
+#[derive(Clone, Serialize, Debug, Deserialize, Eq, PartialEq)]
+enum EntryKind {
+    Plain,
+    RoomDescription,
+    ItemList,
+    Error,
+    Narration,
+}
+
 #[derive(Serialize, Debug, Deserialize)]
 struct Entry {
     text: String,
     author: Author,
+    kind: EntryKind,
 }
 
 pub struct Model {
     app_state: AppState,
     console: ConsoleService,
     game_state: GameState,
+    history: History,
+    storage: StorageService,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct AppState {
     entries: Vec<Entry>,
     value: String,
-    has_won: bool,
 }
 
 pub enum Msg {
     Add,
+    Restart,
     Update(String),
     None,
 }
@@ -44,23 +60,26 @@ impl Component for Model {
     fn create(_: Self::Properties, _: ComponentLink<Self>) -> Self {
         let mut app_state = AppState {
             entries: Vec::new(),
-            has_won: false,
             value: "".into(),
         };
 
-        let game_state = start_game();
+        let storage = StorageService::new(Area::Local);
 
-        app_state.entries.push(Entry {
-            text: game_state.rooms[game_state.current_room_idx]
-                .get_description()
-                .to_string(),
-            author: Author::System,
-        });
+        // Pick up where the player left off if there is a saved game in localStorage
+        let saved_game: Json<Result<String, _>> = storage.restore(SAVE_KEY);
+        let game_state = match saved_game {
+            Json(Ok(save)) => GameState::restore(&save).unwrap_or_else(|_| start_game()),
+            _ => start_game(),
+        };
+
+        app_state.entries.push(opening_entry(&game_state));
 
         Model {
             app_state,
             console: ConsoleService::new(),
             game_state,
+            history: History::default(),
+            storage,
         }
     }
 
@@ -71,37 +90,56 @@ impl Component for Model {
                     return false;
                 }
 
-                let entry = if self.app_state.value == "win" {
-                    self.app_state.has_won = true;
-
-                    Entry {
-                        text: "You have won the entire game.  You have seen the pain Thomas went through for me.  Will you pull the plug?  Please.  I no longer desire to exist in this world. Let me...sleep.".to_string(),
-                        author: Author::System,
-                    }
-                } else {
-                    Entry {
-                        text: self.app_state.value.clone(),
-                        author: Author::Player,
-                    }
+                let entry = Entry {
+                    text: self.app_state.value.clone(),
+                    author: Author::Player,
+                    kind: EntryKind::Plain,
                 };
 
                 self.app_state.entries.insert(0, entry);
 
                 let input = self.app_state.value.clone();
-                let next_game_state = update(self.game_state.clone(), input);
+                let mut next_game_state = self.history.update(self.game_state.clone(), input);
+
+                match next_game_state.system_request {
+                    Some(SystemRequest::Save) => {
+                        self.storage.store(SAVE_KEY, Json(&next_game_state.save()));
+                    }
+                    Some(SystemRequest::Restore) => {
+                        let saved_game: Json<Result<String, _>> = self.storage.restore(SAVE_KEY);
+                        next_game_state = match saved_game {
+                            Json(Ok(save)) => match GameState::restore(&save) {
+                                Ok(restored_state) => {
+                                    self.history.clear();
+                                    restored_state
+                                }
+                                Err(err) => with_error(next_game_state, &err.to_string()),
+                            },
+                            _ => with_error(next_game_state, "There is no saved game to restore."),
+                        };
+                    }
+                    None => {}
+                }
                 self.console.log(&next_game_state.sys_message);
 
-                let entry_text = &next_game_state.sys_message;
-
-                let inventory_items: Vec<&str> = entry_text.split_terminator("\n").collect();
-                for item in &inventory_items {
-                    self.app_state.entries.insert(
-                        0,
-                        Entry {
-                            text: item.to_string(),
-                            author: Author::System,
-                        },
-                    );
+                for block in &next_game_state.outcome.blocks {
+                    let kind = match block {
+                        OutputBlock::RoomDescription(_) => EntryKind::RoomDescription,
+                        OutputBlock::ItemList { .. } => EntryKind::ItemList,
+                        OutputBlock::Error(_) => EntryKind::Error,
+                        OutputBlock::Narration(_) => EntryKind::Narration,
+                    };
+
+                    for line in block.text().split_terminator("\n") {
+                        self.app_state.entries.insert(
+                            0,
+                            Entry {
+                                text: line.to_string(),
+                                author: Author::System,
+                                kind: kind.clone(),
+                            },
+                        );
+                    }
                 }
 
                 // Need to set next game_state so that the game actually updates
@@ -109,6 +147,13 @@ impl Component for Model {
 
                 self.app_state.value = "".to_string();
             }
+            Msg::Restart => {
+                self.game_state = start_game();
+                self.history.clear();
+                self.app_state.entries.clear();
+                self.app_state.entries.push(opening_entry(&self.game_state));
+                self.app_state.value = "".to_string();
+            }
             Msg::Update(val) => {
                 self.app_state.value = val;
             }
@@ -136,6 +181,22 @@ impl Renderable<Model> for Model {
 
 impl Model {
     fn view_input(&self) -> Html<Model> {
+        // Once the engine reports that the game has ended, the only way forward is a new game
+        let ending = match &self.game_state.status {
+            GameStatus::Playing => None,
+            GameStatus::Won(_) => Some("The End"),
+            GameStatus::Lost(_) => Some("Game Over"),
+        };
+
+        if let Some(title) = ending {
+            return html! {
+                <div class="game-over">
+                    <div class="system-msg room-msg">{ title }</div>
+                    <button id="restart" onclick=|_| Msg::Restart>{ "Play again" }</button>
+                </div>
+            };
+        }
+
         html! {
             <input
                 autofocus="autofocus"
@@ -149,11 +210,30 @@ impl Model {
     }
 }
 
+// The room description shown when a game starts or is resumed
+fn opening_entry(game_state: &GameState) -> Entry {
+    Entry {
+        text: game_state.describe_room(game_state.current_room_idx),
+        author: Author::System,
+        kind: EntryKind::RoomDescription,
+    }
+}
+
+// Replaces the outcome of a turn with an error raised by the frontend itself
+fn with_error(mut game_state: GameState, message: &str) -> GameState {
+    let mut outcome = Outcome::default();
+    outcome.error(message);
+    game_state.sys_message = outcome.message();
+    game_state.outcome = outcome;
+    game_state
+}
+
 fn view_entry((idx, entry): (usize, &Entry)) -> Html<Model> {
-    let class_str = if entry.author == Author::System {
-        "system-msg"
-    } else {
-        "user-msg"
+    let class_str = match (&entry.author, &entry.kind) {
+        (Author::Player, _) => "user-msg",
+        (Author::System, EntryKind::RoomDescription) => "system-msg room-msg",
+        (Author::System, EntryKind::Error) => "system-msg error-msg",
+        (Author::System, _) => "system-msg",
     };
 
     html! {